@@ -0,0 +1,65 @@
+use crate::airnet::types::{Episode, ProgramDetails};
+use crate::rss::{CategoryBuilder, ChannelBuilder, Item, ItemBuilder, Rss};
+use crate::{episode_item_builder, episode_title};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Australia::Melbourne;
+
+/// Output directory of the combined feed, alongside the per-program directories.
+pub const COMBINED_FEED_SLUG: &str = "all";
+
+/// Merges the episodes of every program into a single feed, newest first.
+///
+/// Only episodes that started within `window` before `now` are kept, so the
+/// feed stays the same size no matter how far back Airnet goes.
+pub fn combined_feed(programs: &[(ProgramDetails, Vec<Episode>)], window: Duration, now: DateTime<Utc>) -> Rss {
+    let episodes = program_episodes(programs);
+    // Episode times are station local time.
+    let since = now.with_timezone(&Melbourne).naive_local() - window;
+
+    aggregate_feed(
+        ChannelBuilder::new(
+            "PBS 106.7FM - All programs",
+            "https://www.pbsfm.org.au",
+            format!(
+                "Every program aired on PBS 106.7FM in the last {} days",
+                window.num_days()
+            ),
+        ),
+        episodes
+            .into_iter()
+            .filter(|(_, episode)| episode.start >= since)
+            .collect(),
+    )
+}
+
+/// Flattens each program's episodes into `(program, episode)` pairs.
+pub fn program_episodes(programs: &[(ProgramDetails, Vec<Episode>)]) -> Vec<(&ProgramDetails, &Episode)> {
+    programs
+        .iter()
+        .flat_map(|(program, episodes)| episodes.iter().map(move |episode| (program, episode)))
+        .collect()
+}
+
+/// Builds a feed of episodes from several programs, newest first. Each item
+/// title is prefixed with its program name, which is also the item category.
+pub fn aggregate_feed(mut channel: ChannelBuilder, mut episodes: Vec<(&ProgramDetails, &Episode)>) -> Rss {
+    episodes.sort_by_key(|(_, episode)| std::cmp::Reverse(episode.start));
+
+    let items: Vec<Item> = episodes
+        .iter()
+        .map(|(program, episode)| aggregate_item_builder(program, episode).build())
+        .collect();
+
+    Rss::new(channel.language("en".to_owned()).item(items).build())
+}
+
+pub(crate) fn aggregate_item_builder(program: &ProgramDetails, episode: &Episode) -> ItemBuilder {
+    episode_item_builder(program, episode)
+        .title(format!("{}: {}", program.name, episode_title(episode)))
+        .category(
+            CategoryBuilder::new(&program.name)
+                .domain(format!("https://www.pbsfm.org.au/program/{}", program.slug))
+                .build(),
+        )
+        .to_owned()
+}
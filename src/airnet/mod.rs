@@ -1,8 +1,10 @@
 pub mod types;
 
+use std::collections::{HashMap, HashSet};
 use reqwest::blocking::Client;
 use reqwest::Error;
-use crate::airnet::types::{Episode, ProgramDescription, ProgramDetails};
+use chrono::NaiveDateTime;
+use crate::airnet::types::{Episode, PlaylistEntry, ProgramDescription, ProgramDetails};
 
 pub struct AirnetClient {
     client: Client,
@@ -36,7 +38,7 @@ impl AirnetClient {
             program = program,
         );
 
-        self.client.get(req_url).send()?.json()
+        self.client.get(req_url).send()?.error_for_status()?.json()
     }
 
     pub fn episodes(&self, station: &str, program: &str) -> Result<Vec<Episode>, Error> {
@@ -49,4 +51,68 @@ impl AirnetClient {
 
         self.client.get(req_url).send()?.json()
     }
-}
\ No newline at end of file
+
+    pub fn playlist(&self, station: &str, program: &str, start: &NaiveDateTime) -> Result<Vec<PlaylistEntry>, Error> {
+        let req_url = format!(
+            "{base_url}/rest/stations/{station}/programs/{program}/episodes/{start}/playlists",
+            base_url = self.base_url,
+            station = station,
+            program = program,
+            start = episode_path_segment(start),
+        );
+
+        self.client.get(req_url).send()?.json()
+    }
+}
+
+/// Episodes are addressed by their start time, e.g. `2025-06-16+11%3A00%3A00`.
+fn episode_path_segment(start: &NaiveDateTime) -> String {
+    start.format("%Y-%m-%d+%H%%3A%M%%3A%S").to_string()
+}
+
+/// Playlists fetched during a run, keyed by program slug and episode start,
+/// so that each output needing them only hits Airnet once per episode.
+#[derive(Default)]
+pub struct PlaylistCache {
+    playlists: HashMap<(String, NaiveDateTime), Vec<PlaylistEntry>>,
+    /// The keys of the playlists fetched from Airnet, rather than inserted.
+    fetched: HashSet<(String, NaiveDateTime)>,
+}
+
+impl PlaylistCache {
+    pub fn get(&self, program: &str, start: &NaiveDateTime) -> Option<&[PlaylistEntry]> {
+        self.playlists
+            .get(&(program.to_owned(), *start))
+            .map(Vec::as_slice)
+    }
+
+    /// Caches a playlist loaded from elsewhere, such as the archive, so it
+    /// isn't fetched. It isn't one of the `fetched` playlists.
+    pub fn insert(&mut self, program: &str, start: &NaiveDateTime, playlist: Vec<PlaylistEntry>) {
+        let key = (program.to_owned(), *start);
+        self.fetched.remove(&key);
+        self.playlists.insert(key, playlist);
+    }
+
+    /// The playlists fetched from Airnet during the run.
+    pub fn fetched(&self) -> impl Iterator<Item = (&(String, NaiveDateTime), &Vec<PlaylistEntry>)> {
+        self.playlists.iter().filter(|(key, _)| self.fetched.contains(*key))
+    }
+
+    pub fn fetch(
+        &mut self,
+        client: &AirnetClient,
+        station: &str,
+        program: &str,
+        start: &NaiveDateTime,
+    ) -> Result<&[PlaylistEntry], Error> {
+        let key = (program.to_owned(), *start);
+        if !self.playlists.contains_key(&key) {
+            let playlist = client.playlist(station, program, start)?;
+            println!("Fetched playlist: {}, {} entries", start, playlist.len());
+            self.playlists.insert(key.clone(), playlist);
+            self.fetched.insert(key.clone());
+        }
+        Ok(&self.playlists[&key])
+    }
+}
@@ -1,7 +1,7 @@
-use serde::{de, Deserialize, Deserializer};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use chrono::{NaiveDateTime};
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct ProgramDescription {
     pub slug: Option<String>,
     pub name: String,
@@ -50,10 +50,64 @@ pub struct Episode {
     #[serde(deserialize_with = "naive_date_time_from_str")]
     pub end: NaiveDateTime,
     pub duration: u32,
+    /// Set when the program has another episode starting on the same day.
+    #[serde(rename="multipleEpsOnDay", default)]
+    pub multiple_eps_on_day: bool,
     pub title: Option<String>,
     pub description: Option<String>,
     #[serde(rename="imageUrl")]
     pub image_url: Option<String>,
+    /// Set on the episode airing, or next to air, when the episodes were fetched.
+    #[serde(rename="currentEpisode", default)]
+    pub current_episode: bool,
     #[serde(rename="episodeRestUrl")]
     pub episode_rest_url: String,
 }
+
+fn optional_naive_date_time_from_str<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Deserialize::deserialize(deserializer)?;
+    s.map(|s| NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S").map_err(de::Error::custom))
+        .transpose()
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct ContentDescriptors {
+    #[serde(rename="isAustralian")]
+    pub is_australian: Option<bool>,
+    #[serde(rename="isLocal")]
+    pub is_local: Option<bool>,
+    #[serde(rename="isFemale")]
+    pub is_female: Option<bool>,
+    #[serde(rename="isIndigenous")]
+    pub is_indigenous: Option<bool>,
+    #[serde(rename="isNew")]
+    pub is_new: Option<bool>,
+    #[serde(rename="isGenderNonConforming")]
+    pub is_gender_non_conforming: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct PlaylistEntry {
+    #[serde(rename="type")]
+    pub entry_type: String,
+    pub id: u64,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub track: Option<String>,
+    pub release: Option<String>,
+    pub time: Option<String>,
+    pub notes: Option<String>,
+    #[serde(rename="contentDescriptors")]
+    pub content_descriptors: Option<ContentDescriptors>,
+    #[serde(rename="approximateTime", default, deserialize_with = "optional_naive_date_time_from_str")]
+    pub approximate_time: Option<NaiveDateTime>,
+}
+
+impl PlaylistEntry {
+    pub fn is_track(&self) -> bool {
+        self.entry_type == "track"
+    }
+}
@@ -0,0 +1,443 @@
+use crate::airnet::AirnetClient;
+use crate::airnet::types::{ContentDescriptors, Episode, PlaylistEntry, ProgramDescription, ProgramDetails};
+use crate::{PBSFM_STATION, enclosure_url, episode_link, episode_title, is_slug, live, rm_query_params, station_datetime};
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::fmt;
+use std::str::FromStr;
+
+/// The path the JSON API is served under.
+pub const API_PATH: &str = "/api";
+
+/// A program as listed on the station.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ApiProgramSummary {
+    pub slug: Option<String>,
+    pub name: String,
+    pub broadcasters: String,
+    pub grid_description: Option<String>,
+    pub archived: bool,
+    pub link: Option<String>,
+}
+
+/// A program's details, with its artwork stripped of Airnet's cache-busting
+/// query parameters.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ApiProgram {
+    pub slug: String,
+    pub name: String,
+    pub broadcasters: String,
+    pub description: String,
+    pub grid_description: Option<String>,
+    pub link: String,
+    pub artwork_url: String,
+    pub banner_url: Option<String>,
+}
+
+/// An episode with timezone-aware times and the URLs feeds use for it.
+/// `enclosure_url` is only set once the episode has aired.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ApiEpisode {
+    pub id: String,
+    pub program: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub start: String,
+    pub end: String,
+    pub duration: u32,
+    pub link: String,
+    pub enclosure_url: Option<String>,
+    pub image_url: Option<String>,
+    pub playlist_url: String,
+}
+
+/// A playlist entry, with its approximate time made timezone-aware.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ApiPlaylistEntry {
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    pub id: u64,
+    pub artist: Option<String>,
+    pub title: Option<String>,
+    pub release: Option<String>,
+    pub notes: Option<String>,
+    pub approximate_time: Option<String>,
+    pub australian: Option<bool>,
+    pub local: Option<bool>,
+    pub female: Option<bool>,
+    pub indigenous: Option<bool>,
+    pub new: Option<bool>,
+    pub gender_non_conforming: Option<bool>,
+}
+
+/// Identifies an episode by program and start, e.g. `black-wax.20250616T110000`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EpisodeId {
+    pub program: String,
+    pub start: NaiveDateTime,
+}
+
+const EPISODE_ID_START_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+impl fmt::Display for EpisodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.program, self.start.format(EPISODE_ID_START_FORMAT))
+    }
+}
+
+impl FromStr for EpisodeId {
+    type Err = String;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let (program, start) = id.split_once('.').ok_or_else(|| format!("invalid episode id: {id}"))?;
+        if !is_slug(program) {
+            return Err(format!("invalid episode id: {id}"));
+        }
+        let start = NaiveDateTime::parse_from_str(start, EPISODE_ID_START_FORMAT)
+            .map_err(|err| format!("invalid episode id: {id}: {err}"))?;
+        Ok(EpisodeId { program: program.to_owned(), start })
+    }
+}
+
+/// The requests the API answers.
+#[derive(Debug, PartialEq)]
+pub enum ApiRoute {
+    Programs,
+    Program(String),
+    Episodes(String),
+    Playlist(EpisodeId),
+    OpenApi,
+}
+
+impl ApiRoute {
+    /// The route for a path under `/api`, or `None` if there is none.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let rest = path.strip_prefix(API_PATH)?.trim_end_matches('/');
+        let segments: Vec<&str> = rest.trim_start_matches('/').split('/').collect();
+        match segments.as_slice() {
+            ["programs"] => Some(ApiRoute::Programs),
+            ["programs", slug] if is_slug(slug) => Some(ApiRoute::Program(slug.to_string())),
+            ["programs", slug, "episodes"] if is_slug(slug) => Some(ApiRoute::Episodes(slug.to_string())),
+            ["episodes", id, "playlist"] => id.parse().ok().map(ApiRoute::Playlist),
+            ["openapi.json"] => Some(ApiRoute::OpenApi),
+            _ => None,
+        }
+    }
+
+    /// The program the route is about, if any, for checking against an allow-list.
+    pub fn program(&self) -> Option<&str> {
+        match self {
+            ApiRoute::Program(slug) | ApiRoute::Episodes(slug) => Some(slug),
+            ApiRoute::Playlist(id) => Some(&id.program),
+            ApiRoute::Programs | ApiRoute::OpenApi => None,
+        }
+    }
+
+    /// Fetches what the route asks for from Airnet, as JSON. Only the
+    /// `programs` allowed are listed, or every program when there are none.
+    pub fn fetch(
+        &self,
+        airnet_url: &str,
+        programs: &[String],
+        now: DateTime<Utc>,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let client = AirnetClient::new(airnet_url.to_owned());
+        let body = match self {
+            ApiRoute::Programs => {
+                let summaries: Vec<ApiProgramSummary> = client
+                    .all_programs(PBSFM_STATION)?
+                    .iter()
+                    .filter(|program| {
+                        programs.is_empty() || program.slug.as_ref().is_some_and(|slug| programs.contains(slug))
+                    })
+                    .map(ApiProgramSummary::from)
+                    .collect();
+                serde_json::to_vec_pretty(&summaries)?
+            }
+            ApiRoute::Program(slug) => serde_json::to_vec_pretty(&ApiProgram::new(&client.program(PBSFM_STATION, slug)?)?)?,
+            ApiRoute::Episodes(slug) => {
+                let episodes = client.episodes(PBSFM_STATION, slug)?;
+                let episodes: Vec<ApiEpisode> = episodes.iter().map(|episode| ApiEpisode::new(slug, episode, now)).collect();
+                serde_json::to_vec_pretty(&episodes)?
+            }
+            ApiRoute::Playlist(id) => {
+                let playlist = client.playlist(PBSFM_STATION, &id.program, &id.start)?;
+                serde_json::to_vec_pretty(&playlist.iter().map(ApiPlaylistEntry::from).collect::<Vec<_>>())?
+            }
+            ApiRoute::OpenApi => serde_json::to_vec_pretty(&openapi_document())?,
+        };
+        Ok(body)
+    }
+}
+
+fn api_datetime(time: &NaiveDateTime) -> String {
+    station_datetime(time).to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+impl From<&ProgramDescription> for ApiProgramSummary {
+    fn from(program: &ProgramDescription) -> Self {
+        ApiProgramSummary {
+            slug: program.slug.clone(),
+            name: program.name.clone(),
+            broadcasters: program.broadcasters.clone(),
+            grid_description: program.grid_description.clone(),
+            archived: program.archived,
+            link: program.slug.as_ref().map(|slug| format!("https://www.pbsfm.org.au/program/{slug}")),
+        }
+    }
+}
+
+impl ApiProgram {
+    pub fn new(program: &ProgramDetails) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(ApiProgram {
+            slug: program.slug.clone(),
+            name: program.name.clone(),
+            broadcasters: program.broadcasters.clone(),
+            description: program.description.clone(),
+            grid_description: program.grid_description.clone(),
+            link: format!("https://www.pbsfm.org.au/program/{}", program.slug),
+            artwork_url: rm_query_params(&program.profile_image_url)?,
+            banner_url: program.banner_image_url.as_deref().map(rm_query_params).transpose()?,
+        })
+    }
+}
+
+impl ApiEpisode {
+    pub fn new(program_slug: &str, episode: &Episode, now: DateTime<Utc>) -> Self {
+        let id = EpisodeId { program: program_slug.to_owned(), start: episode.start };
+        ApiEpisode {
+            program: program_slug.to_owned(),
+            title: episode_title(episode),
+            description: episode.description.clone(),
+            start: api_datetime(&episode.start),
+            end: api_datetime(&episode.end),
+            duration: episode.duration,
+            link: episode_link(program_slug, &episode.start),
+            enclosure_url: live::has_aired(episode, now).then(|| enclosure_url(program_slug, &episode.start)),
+            image_url: episode.image_url.as_deref().and_then(|url| rm_query_params(url).ok()),
+            playlist_url: format!("{API_PATH}/episodes/{id}/playlist"),
+            id: id.to_string(),
+        }
+    }
+}
+
+impl From<&PlaylistEntry> for ApiPlaylistEntry {
+    fn from(entry: &PlaylistEntry) -> Self {
+        let descriptors = entry.content_descriptors.as_ref();
+        let descriptor = |flag: fn(&ContentDescriptors) -> Option<bool>| descriptors.and_then(flag);
+        ApiPlaylistEntry {
+            entry_type: entry.entry_type.clone(),
+            id: entry.id,
+            artist: entry.artist.clone(),
+            title: entry.title.clone().or_else(|| entry.track.clone()),
+            release: entry.release.clone(),
+            notes: entry.notes.clone(),
+            approximate_time: entry.approximate_time.as_ref().map(api_datetime),
+            australian: descriptor(|d| d.is_australian),
+            local: descriptor(|d| d.is_local),
+            female: descriptor(|d| d.is_female),
+            indigenous: descriptor(|d| d.is_indigenous),
+            new: descriptor(|d| d.is_new),
+            gender_non_conforming: descriptor(|d| d.is_gender_non_conforming),
+        }
+    }
+}
+
+/// The OpenAPI 3.1 description of the API, for generating clients.
+pub fn openapi_document() -> Value {
+    let nullable_string = json!({ "type": ["string", "null"] });
+    let nullable_bool = json!({ "type": ["boolean", "null"] });
+    let date_time = json!({ "type": "string", "format": "date-time", "description": "RFC 3339, in Melbourne time" });
+    let slug = json!({ "name": "slug", "in": "path", "required": true, "schema": { "type": "string", "pattern": "^[a-z0-9-]+$" } });
+    let json_response = |description: &str, schema: Value| {
+        json!({
+            "description": description,
+            "content": { "application/json": { "schema": schema } },
+        })
+    };
+    let errors = json!({
+        "304": { "description": "Not modified since the `If-None-Match` entity tag" },
+        "404": { "description": "No such route, or the program isn't served" },
+        "502": { "description": "Airnet could not be reached" },
+    });
+    let operation = |summary: &str, parameters: Vec<Value>, ok: Value| {
+        let mut responses = errors.clone();
+        responses["200"] = ok;
+        json!({ "summary": summary, "parameters": parameters, "responses": responses })
+    };
+    let array_of = |schema: &str| json!({ "type": "array", "items": { "$ref": format!("#/components/schemas/{schema}") } });
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "PBS FM programs API",
+            "description": "Read-only PBS FM programs, episodes and playlists from Airnet, normalized as in the generated feeds.",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/api/programs": {
+                "get": operation("List every program on the station", vec![], json_response("Programs", array_of("ProgramSummary"))),
+            },
+            "/api/programs/{slug}": {
+                "get": operation(
+                    "Get a program's details",
+                    vec![slug.clone()],
+                    json_response("Program", json!({ "$ref": "#/components/schemas/Program" })),
+                ),
+            },
+            "/api/programs/{slug}/episodes": {
+                "get": operation("List a program's recent episodes", vec![slug], json_response("Episodes", array_of("Episode"))),
+            },
+            "/api/episodes/{id}/playlist": {
+                "get": operation(
+                    "Get an episode's playlist",
+                    vec![json!({
+                        "name": "id",
+                        "in": "path",
+                        "required": true,
+                        "description": "Program slug and start, e.g. `black-wax.20250616T110000`",
+                        "schema": { "type": "string" },
+                    })],
+                    json_response("Playlist entries", array_of("PlaylistEntry")),
+                ),
+            },
+        },
+        "components": {
+            "schemas": {
+                "ProgramSummary": {
+                    "type": "object",
+                    "required": ["slug", "name", "broadcasters", "grid_description", "archived", "link"],
+                    "properties": {
+                        "slug": nullable_string,
+                        "name": { "type": "string" },
+                        "broadcasters": { "type": "string" },
+                        "grid_description": nullable_string,
+                        "archived": { "type": "boolean" },
+                        "link": nullable_string,
+                    },
+                },
+                "Program": {
+                    "type": "object",
+                    "required": ["slug", "name", "broadcasters", "description", "grid_description", "link", "artwork_url", "banner_url"],
+                    "properties": {
+                        "slug": { "type": "string" },
+                        "name": { "type": "string" },
+                        "broadcasters": { "type": "string" },
+                        "description": { "type": "string" },
+                        "grid_description": nullable_string,
+                        "link": { "type": "string", "format": "uri" },
+                        "artwork_url": { "type": "string", "format": "uri" },
+                        "banner_url": nullable_string,
+                    },
+                },
+                "Episode": {
+                    "type": "object",
+                    "required": [
+                        "id", "program", "title", "description", "start", "end", "duration",
+                        "link", "enclosure_url", "image_url", "playlist_url",
+                    ],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "program": { "type": "string" },
+                        "title": { "type": "string" },
+                        "description": nullable_string,
+                        "start": date_time,
+                        "end": date_time,
+                        "duration": { "type": "integer", "description": "Seconds" },
+                        "link": { "type": "string", "format": "uri" },
+                        "enclosure_url": { "type": ["string", "null"], "description": "Null until the episode has aired" },
+                        "image_url": nullable_string,
+                        "playlist_url": { "type": "string" },
+                    },
+                },
+                "PlaylistEntry": {
+                    "type": "object",
+                    "required": ["type", "id"],
+                    "properties": {
+                        "type": { "type": "string", "examples": ["track", "break"] },
+                        "id": { "type": "integer" },
+                        "artist": nullable_string,
+                        "title": nullable_string,
+                        "release": nullable_string,
+                        "notes": nullable_string,
+                        "approximate_time": { "type": ["string", "null"], "format": "date-time" },
+                        "australian": nullable_bool,
+                        "local": nullable_bool,
+                        "female": nullable_bool,
+                        "indigenous": nullable_bool,
+                        "new": nullable_bool,
+                        "gender_non_conforming": nullable_bool,
+                    },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::TimeZone;
+
+    fn start() -> NaiveDateTime {
+        test_support::datetime(6, 16, 11)
+    }
+
+    #[test]
+    fn test_episode_id() {
+        let id = EpisodeId { program: "black-wax".to_owned(), start: start() };
+        assert_eq!(id.to_string(), "black-wax.20250616T110000");
+        assert_eq!("black-wax.20250616T110000".parse(), Ok(id));
+        assert!("black-wax".parse::<EpisodeId>().is_err());
+        assert!("../x.20250616T110000".parse::<EpisodeId>().is_err());
+    }
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(ApiRoute::from_path("/api/programs"), Some(ApiRoute::Programs));
+        assert_eq!(ApiRoute::from_path("/api/programs/black-wax/"), Some(ApiRoute::Program("black-wax".to_owned())));
+        assert_eq!(ApiRoute::from_path("/api/programs/black-wax/episodes"), Some(ApiRoute::Episodes("black-wax".to_owned())));
+        assert_eq!(
+            ApiRoute::from_path("/api/episodes/black-wax.20250616T110000/playlist"),
+            Some(ApiRoute::Playlist(EpisodeId { program: "black-wax".to_owned(), start: start() }))
+        );
+        assert_eq!(ApiRoute::from_path("/api/episodes/black-wax/playlist"), None);
+        assert_eq!(ApiRoute::from_path("/feeds/pbsfm/black-wax"), None);
+    }
+
+    #[test]
+    fn test_episode() {
+        let episode = Episode {
+            image_url: Some("https://img.jpg?cacbeb=1".to_owned()),
+            ..test_support::episode(start())
+        };
+        let aired = Utc.with_ymd_and_hms(2025, 6, 17, 0, 0, 0).unwrap();
+        let api_episode = ApiEpisode::new("black-wax", &episode, aired);
+        assert_eq!(api_episode.id, "black-wax.20250616T110000");
+        assert_eq!(api_episode.title, "Untitled - 2025-06-16");
+        assert_eq!(api_episode.start, "2025-06-16T11:00:00+10:00");
+        assert_eq!(api_episode.end, "2025-06-16T13:00:00+10:00");
+        assert_eq!(api_episode.image_url.as_deref(), Some("https://img.jpg"));
+        assert_eq!(
+            api_episode.enclosure_url.as_deref(),
+            Some("https://airnet.org.au/omnystudio/3pbs/black-wax/2025-06-16+11:00:00/aac_mid.m4a")
+        );
+        assert_eq!(api_episode.playlist_url, "/api/episodes/black-wax.20250616T110000/playlist");
+
+        let airing = Utc.with_ymd_and_hms(2025, 6, 16, 2, 0, 0).unwrap();
+        assert_eq!(ApiEpisode::new("black-wax", &episode, airing).enclosure_url, None);
+    }
+
+    #[test]
+    fn test_openapi_document_describes_every_route() {
+        let document = openapi_document();
+        let paths = document["paths"].as_object().unwrap();
+        for path in paths.keys() {
+            let example = path.replace("{slug}", "black-wax").replace("{id}", "black-wax.20250616T110000");
+            assert!(ApiRoute::from_path(&example).is_some(), "{path} is not routed");
+        }
+        assert_eq!(paths.len(), 4);
+    }
+}
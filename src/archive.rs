@@ -0,0 +1,591 @@
+use crate::airnet::types::{
+    ContentDescriptors, Episode, PlaylistEntry, ProgramDescription, ProgramDetails,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Row, ToSql, params, params_from_iter};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS program_descriptions (
+    program_rest_url TEXT PRIMARY KEY,
+    slug TEXT,
+    name TEXT NOT NULL,
+    broadcasters TEXT NOT NULL,
+    grid_description TEXT,
+    archived INTEGER NOT NULL,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS programs (
+    slug TEXT PRIMARY KEY,
+    url TEXT,
+    name TEXT NOT NULL,
+    broadcasters TEXT NOT NULL,
+    description TEXT NOT NULL,
+    grid_description TEXT,
+    banner_image_url TEXT,
+    banner_image_small TEXT,
+    profile_image_url TEXT NOT NULL,
+    profile_image_small TEXT NOT NULL,
+    episodes_rest_url TEXT NOT NULL,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS episodes (
+    program_slug TEXT NOT NULL,
+    start TEXT NOT NULL,
+    end TEXT NOT NULL,
+    duration INTEGER NOT NULL,
+    url TEXT,
+    title TEXT,
+    description TEXT,
+    image_url TEXT,
+    episode_rest_url TEXT NOT NULL,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    PRIMARY KEY (program_slug, start)
+);
+CREATE TABLE IF NOT EXISTS tracks (
+    program_slug TEXT NOT NULL,
+    episode_start TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    entry_type TEXT NOT NULL,
+    artist TEXT,
+    title TEXT,
+    track TEXT,
+    release TEXT,
+    time TEXT,
+    notes TEXT,
+    approximate_time TEXT,
+    is_australian INTEGER,
+    is_local INTEGER,
+    is_female INTEGER,
+    is_indigenous INTEGER,
+    is_new INTEGER,
+    is_gender_non_conforming INTEGER,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    PRIMARY KEY (program_slug, episode_start, id)
+);
+";
+
+/// A local SQLite archive of everything fetched from Airnet.
+///
+/// Rows are upserted on every run: `first_seen` records when a program,
+/// episode or track was first fetched and `last_seen` the most recent time,
+/// so episodes that have dropped out of Airnet's window are kept.
+pub struct Archive {
+    connection: Connection,
+}
+
+impl Archive {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> rusqlite::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Archive { connection })
+    }
+
+    pub fn record_program_descriptions(
+        &self,
+        programs: &[ProgramDescription],
+        seen_at: DateTime<Utc>,
+    ) -> rusqlite::Result<()> {
+        let mut statement = self.connection.prepare_cached(
+            "INSERT INTO program_descriptions
+                (program_rest_url, slug, name, broadcasters, grid_description, archived, first_seen, last_seen)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+            ON CONFLICT (program_rest_url) DO UPDATE SET
+                slug = excluded.slug, name = excluded.name, broadcasters = excluded.broadcasters,
+                grid_description = excluded.grid_description, archived = excluded.archived,
+                last_seen = excluded.last_seen",
+        )?;
+        for program in programs {
+            statement.execute(params![
+                program.program_rest_url,
+                program.slug,
+                program.name,
+                program.broadcasters,
+                program.grid_description,
+                program.archived,
+                seen_at,
+            ])?;
+        }
+        Ok(())
+    }
+
+    pub fn record_program(&self, program: &ProgramDetails, seen_at: DateTime<Utc>) -> rusqlite::Result<()> {
+        self.connection.execute(
+            "INSERT INTO programs
+                (slug, url, name, broadcasters, description, grid_description, banner_image_url,
+                banner_image_small, profile_image_url, profile_image_small, episodes_rest_url, first_seen, last_seen)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?12)
+            ON CONFLICT (slug) DO UPDATE SET
+                url = excluded.url, name = excluded.name, broadcasters = excluded.broadcasters,
+                description = excluded.description, grid_description = excluded.grid_description,
+                banner_image_url = excluded.banner_image_url, banner_image_small = excluded.banner_image_small,
+                profile_image_url = excluded.profile_image_url, profile_image_small = excluded.profile_image_small,
+                episodes_rest_url = excluded.episodes_rest_url, last_seen = excluded.last_seen",
+            params![
+                program.slug,
+                program.url,
+                program.name,
+                program.broadcasters,
+                program.description,
+                program.grid_description,
+                program.banner_image_url,
+                program.banner_image_small,
+                program.profile_image_url,
+                program.profile_image_small,
+                program.episodes_rest_url,
+                seen_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_episodes(
+        &self,
+        program_slug: &str,
+        episodes: &[Episode],
+        seen_at: DateTime<Utc>,
+    ) -> rusqlite::Result<()> {
+        let mut statement = self.connection.prepare_cached(
+            "INSERT INTO episodes
+                (program_slug, start, end, duration, url, title, description, image_url, episode_rest_url, first_seen, last_seen)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)
+            ON CONFLICT (program_slug, start) DO UPDATE SET
+                end = excluded.end, duration = excluded.duration, url = excluded.url, title = excluded.title,
+                description = excluded.description, image_url = excluded.image_url,
+                episode_rest_url = excluded.episode_rest_url, last_seen = excluded.last_seen",
+        )?;
+        for episode in episodes {
+            statement.execute(params![
+                program_slug,
+                episode.start,
+                episode.end,
+                episode.duration,
+                episode.url,
+                episode.title,
+                episode.description,
+                episode.image_url,
+                episode.episode_rest_url,
+                seen_at,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Records the episode's playlist as Airnet now lists it, removing tracks
+    /// Airnet has since taken out of it.
+    pub fn record_playlist(
+        &self,
+        program_slug: &str,
+        episode_start: &NaiveDateTime,
+        playlist: &[PlaylistEntry],
+        seen_at: DateTime<Utc>,
+    ) -> rusqlite::Result<()> {
+        let ids: Vec<u64> = playlist.iter().map(|entry| entry.id).collect();
+        self.connection.execute(
+            "DELETE FROM tracks WHERE program_slug = ?1 AND episode_start = ?2
+                AND id NOT IN (SELECT value FROM json_each(?3))",
+            params![program_slug, episode_start, serde_json::to_string(&ids).expect("Ids serialize to JSON.")],
+        )?;
+        let mut statement = self.connection.prepare_cached(
+            "INSERT INTO tracks
+                (program_slug, episode_start, id, position, entry_type, artist, title, track, release, time, notes,
+                approximate_time, is_australian, is_local, is_female, is_indigenous, is_new, is_gender_non_conforming,
+                first_seen, last_seen)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?19)
+            ON CONFLICT (program_slug, episode_start, id) DO UPDATE SET
+                position = excluded.position, entry_type = excluded.entry_type, artist = excluded.artist,
+                title = excluded.title, track = excluded.track, release = excluded.release, time = excluded.time,
+                notes = excluded.notes, approximate_time = excluded.approximate_time,
+                is_australian = excluded.is_australian, is_local = excluded.is_local,
+                is_female = excluded.is_female, is_indigenous = excluded.is_indigenous, is_new = excluded.is_new,
+                is_gender_non_conforming = excluded.is_gender_non_conforming, last_seen = excluded.last_seen",
+        )?;
+        for (position, entry) in playlist.iter().enumerate() {
+            let descriptors = entry.content_descriptors.as_ref();
+            statement.execute(params![
+                program_slug,
+                episode_start,
+                entry.id,
+                position,
+                entry.entry_type,
+                entry.artist,
+                entry.title,
+                entry.track,
+                entry.release,
+                entry.time,
+                entry.notes,
+                entry.approximate_time,
+                descriptors.and_then(|d| d.is_australian),
+                descriptors.and_then(|d| d.is_local),
+                descriptors.and_then(|d| d.is_female),
+                descriptors.and_then(|d| d.is_indigenous),
+                descriptors.and_then(|d| d.is_new),
+                descriptors.and_then(|d| d.is_gender_non_conforming),
+                seen_at,
+            ])?;
+        }
+        Ok(())
+    }
+
+    pub fn program(&self, slug: &str) -> rusqlite::Result<Option<ProgramDetails>> {
+        self.connection
+            .query_row(
+                "SELECT url, name, broadcasters, description, grid_description, slug, banner_image_url,
+                    banner_image_small, profile_image_url, profile_image_small, episodes_rest_url
+                FROM programs WHERE slug = ?1",
+                params![slug],
+                |row| {
+                    Ok(ProgramDetails {
+                        url: row.get(0)?,
+                        name: row.get(1)?,
+                        broadcasters: row.get(2)?,
+                        description: row.get(3)?,
+                        grid_description: row.get(4)?,
+                        slug: row.get(5)?,
+                        banner_image_url: row.get(6)?,
+                        banner_image_small: row.get(7)?,
+                        profile_image_url: row.get(8)?,
+                        profile_image_small: row.get(9)?,
+                        episodes_rest_url: row.get(10)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// Every archived episode of the program, oldest first like Airnet's episode list.
+    pub fn episodes(&self, program_slug: &str) -> rusqlite::Result<Vec<Episode>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {EPISODE_COLUMNS} FROM episodes e WHERE e.program_slug = ?1 ORDER BY e.start"
+        ))?;
+        let mut episodes: Vec<Episode> = statement
+            .query_map(params![program_slug], |row| episode(row, 0))?
+            .collect::<rusqlite::Result<_>>()?;
+        // Airnet's flag isn't archived; it follows from the other archived episodes.
+        let days: Vec<NaiveDate> = episodes.iter().map(|episode| episode.start.date()).collect();
+        for episode in &mut episodes {
+            let day = episode.start.date();
+            episode.multiple_eps_on_day = days.iter().filter(|other| **other == day).count() > 1;
+        }
+        Ok(episodes)
+    }
+
+    /// The archived playlist of an episode, in broadcast order. Empty when it was never fetched.
+    pub fn playlist(&self, program_slug: &str, episode_start: &NaiveDateTime) -> rusqlite::Result<Vec<PlaylistEntry>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT {TRACK_COLUMNS} FROM tracks t
+            WHERE t.program_slug = ?1 AND t.episode_start = ?2 ORDER BY t.position"
+        ))?;
+        statement
+            .query_map(params![program_slug, episode_start], |row| playlist_entry(row, 0))?
+            .collect()
+    }
+}
+
+/// Filters for [`Archive::search`]. Text filters are case-insensitive
+/// substring matches; empty filters match everything.
+#[derive(Debug, Default, Clone)]
+pub struct SearchQuery {
+    pub artist: Option<String>,
+    pub track: Option<String>,
+    pub release: Option<String>,
+    /// Matched against the episode title and description.
+    pub text: Option<String>,
+    pub programs: Vec<String>,
+    pub from: Option<NaiveDate>,
+    /// Inclusive.
+    pub to: Option<NaiveDate>,
+}
+
+impl SearchQuery {
+    fn searches_tracks(&self) -> bool {
+        self.artist.is_some() || self.track.is_some() || self.release.is_some()
+    }
+}
+
+/// An archived episode matching a search, with the matching track when the
+/// search was for an artist, track or release.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SearchMatch {
+    pub program_slug: String,
+    pub program_name: Option<String>,
+    pub episode: Episode,
+    pub track: Option<PlaylistEntry>,
+}
+
+const EPISODE_COLUMNS: &str = "e.url, e.start, e.end, e.duration, e.title, e.description, e.image_url, e.episode_rest_url";
+const TRACK_COLUMNS: &str = "t.entry_type, t.id, t.artist, t.title, t.track, t.release, t.time, t.notes, t.approximate_time,
+    t.is_australian, t.is_local, t.is_female, t.is_indigenous, t.is_new, t.is_gender_non_conforming";
+
+impl Archive {
+    /// Searches archived episodes, newest first, or their tracks in broadcast
+    /// order when the query has an artist, track or release.
+    pub fn search(&self, query: &SearchQuery) -> rusqlite::Result<Vec<SearchMatch>> {
+        let mut conditions: Vec<String> = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+        let mut like = |condition: &str, value: &Option<String>| {
+            if let Some(value) = value {
+                let escaped = value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+                values.push(Box::new(format!("%{escaped}%")));
+                conditions.push(condition.replace('?', &format!("?{}", values.len())));
+            }
+        };
+        like("t.artist LIKE ? ESCAPE '\\'", &query.artist);
+        like("COALESCE(t.track, t.title) LIKE ? ESCAPE '\\'", &query.track);
+        like("t.release LIKE ? ESCAPE '\\'", &query.release);
+        like("(e.title LIKE ? ESCAPE '\\' OR e.description LIKE ? ESCAPE '\\')", &query.text);
+
+        if !query.programs.is_empty() {
+            let mut placeholders = Vec::new();
+            for program in &query.programs {
+                values.push(Box::new(program.clone()));
+                placeholders.push(format!("?{}", values.len()));
+            }
+            conditions.push(format!("e.program_slug IN ({})", placeholders.join(", ")));
+        }
+        if let Some(from) = query.from {
+            values.push(Box::new(from.and_time(NaiveTime::MIN)));
+            conditions.push(format!("e.start >= ?{}", values.len()));
+        }
+        if let Some(to) = query.to.and_then(|to| to.succ_opt()) {
+            values.push(Box::new(to.and_time(NaiveTime::MIN)));
+            conditions.push(format!("e.start < ?{}", values.len()));
+        }
+
+        let searches_tracks = query.searches_tracks();
+        let sql = format!(
+            "SELECT e.program_slug, p.name, {EPISODE_COLUMNS}{track_columns}
+            FROM episodes e
+            LEFT JOIN programs p ON p.slug = e.program_slug
+            {track_join}
+            WHERE {conditions}
+            ORDER BY e.start DESC{track_order}",
+            track_columns = if searches_tracks { format!(", {TRACK_COLUMNS}") } else { String::new() },
+            track_join = if searches_tracks {
+                "JOIN tracks t ON t.program_slug = e.program_slug AND t.episode_start = e.start"
+            } else {
+                ""
+            },
+            conditions = if conditions.is_empty() { "1".to_owned() } else { conditions.join(" AND ") },
+            track_order = if searches_tracks { ", t.position" } else { "" },
+        );
+
+        let mut statement = self.connection.prepare(&sql)?;
+        statement
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok(SearchMatch {
+                    program_slug: row.get(0)?,
+                    program_name: row.get(1)?,
+                    episode: episode(row, 2)?,
+                    track: if searches_tracks { Some(playlist_entry(row, 10)?) } else { None },
+                })
+            })?
+            .collect()
+    }
+}
+
+fn episode(row: &Row, offset: usize) -> rusqlite::Result<Episode> {
+    Ok(Episode {
+        url: row.get(offset)?,
+        start: row.get(offset + 1)?,
+        end: row.get(offset + 2)?,
+        duration: row.get(offset + 3)?,
+        multiple_eps_on_day: false,
+        title: row.get(offset + 4)?,
+        description: row.get(offset + 5)?,
+        image_url: row.get(offset + 6)?,
+        current_episode: false,
+        episode_rest_url: row.get(offset + 7)?,
+    })
+}
+
+fn playlist_entry(row: &Row, offset: usize) -> rusqlite::Result<PlaylistEntry> {
+    Ok(PlaylistEntry {
+        entry_type: row.get(offset)?,
+        id: row.get(offset + 1)?,
+        artist: row.get(offset + 2)?,
+        title: row.get(offset + 3)?,
+        track: row.get(offset + 4)?,
+        release: row.get(offset + 5)?,
+        time: row.get(offset + 6)?,
+        notes: row.get(offset + 7)?,
+        approximate_time: row.get(offset + 8)?,
+        content_descriptors: Some(ContentDescriptors {
+            is_australian: row.get(offset + 9)?,
+            is_local: row.get(offset + 10)?,
+            is_female: row.get(offset + 11)?,
+            is_indigenous: row.get(offset + 12)?,
+            is_new: row.get(offset + 13)?,
+            is_gender_non_conforming: row.get(offset + 14)?,
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::TimeZone;
+
+    fn program() -> ProgramDetails {
+        ProgramDetails {
+            name: "Black Wax".to_owned(),
+            broadcasters: "Adam Rudegeair".to_owned(),
+            description: "Jazz".to_owned(),
+            grid_description: Some("Groovin' jazz".to_owned()),
+            profile_image_url: "https://profile-img.jpg".to_owned(),
+            profile_image_small: "https://profile-img-small.jpg".to_owned(),
+            episodes_rest_url: "https://episodes".to_owned(),
+            ..test_support::program("black-wax")
+        }
+    }
+
+    fn episode(day: u32, title: &str) -> Episode {
+        Episode {
+            title: Some(title.to_owned()),
+            episode_rest_url: format!("https://episodes/{day}"),
+            ..test_support::episode(test_support::datetime(8, day, 11))
+        }
+    }
+
+    fn seen_at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 9, day, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_program_round_trip() -> rusqlite::Result<()> {
+        let archive = Archive::open_in_memory()?;
+        archive.record_program(&program(), seen_at(1))?;
+        archive.record_program(&program(), seen_at(2))?;
+
+        assert_eq!(archive.program("black-wax")?, Some(program()));
+        assert_eq!(archive.program("not-exists")?, None);
+        let seen: (DateTime<Utc>, DateTime<Utc>) = archive.connection.query_row(
+            "SELECT first_seen, last_seen FROM programs WHERE slug = 'black-wax'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        assert_eq!(seen, (seen_at(1), seen_at(2)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_program_descriptions_upserted_by_rest_url() -> rusqlite::Result<()> {
+        let archive = Archive::open_in_memory()?;
+        let description = ProgramDescription {
+            slug: Some("black-wax".to_owned()),
+            name: "Black Wax".to_owned(),
+            broadcasters: "Adam Rudegeair".to_owned(),
+            grid_description: Some("Groovin' jazz".to_owned()),
+            archived: false,
+            program_rest_url: "https://airnet/programs/black-wax".to_owned(),
+        };
+        archive.record_program_descriptions(std::slice::from_ref(&description), seen_at(1))?;
+        let archived = ProgramDescription { slug: None, archived: true, ..description };
+        archive.record_program_descriptions(&[archived], seen_at(2))?;
+
+        let row: (Option<String>, bool, DateTime<Utc>, DateTime<Utc>) = archive.connection.query_row(
+            "SELECT slug, archived, first_seen, last_seen FROM program_descriptions",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
+        assert_eq!(row, (None, true, seen_at(1), seen_at(2)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_episodes_accumulate_across_runs() -> rusqlite::Result<()> {
+        let archive = Archive::open_in_memory()?;
+        archive.record_episodes("black-wax", &[episode(18, "old"), episode(25, "first title")], seen_at(1))?;
+        archive.record_episodes("black-wax", &[episode(25, "new title")], seen_at(2))?;
+
+        assert_eq!(
+            archive.episodes("black-wax")?,
+            vec![episode(18, "old"), episode(25, "new title")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_episodes_on_day() -> rusqlite::Result<()> {
+        let archive = Archive::open_in_memory()?;
+        let evening = Episode {
+            start: episode(25, "evening").start + chrono::Duration::hours(8),
+            end: episode(25, "evening").end + chrono::Duration::hours(8),
+            ..episode(25, "evening")
+        };
+        archive.record_episodes("black-wax", &[episode(18, "old"), episode(25, "morning"), evening], seen_at(1))?;
+
+        let flags: Vec<bool> = archive.episodes("black-wax")?.iter().map(|episode| episode.multiple_eps_on_day).collect();
+        assert_eq!(flags, vec![false, true, true]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_playlist_round_trip() -> rusqlite::Result<()> {
+        let archive = Archive::open_in_memory()?;
+        let start = episode(25, "title").start;
+        let entry = |id: u64, artist: &str| PlaylistEntry {
+            entry_type: "track".to_owned(),
+            id,
+            artist: Some(artist.to_owned()),
+            title: Some("title".to_owned()),
+            track: Some("title".to_owned()),
+            release: None,
+            time: Some("11:00:00".to_owned()),
+            notes: None,
+            content_descriptors: Some(ContentDescriptors {
+                is_australian: Some(true),
+                is_local: None,
+                is_female: Some(false),
+                is_indigenous: None,
+                is_new: None,
+                is_gender_non_conforming: None,
+            }),
+            approximate_time: Some(start),
+        };
+        let playlist = vec![entry(20, "second id, played first"), entry(10, "first id, played second")];
+        archive.record_playlist("black-wax", &start, &playlist, seen_at(1))?;
+
+        assert_eq!(archive.playlist("black-wax", &start)?, playlist);
+        assert_eq!(archive.playlist("black-wax", &episode(18, "title").start)?, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_playlist_removes_dropped_tracks() -> rusqlite::Result<()> {
+        let archive = Archive::open_in_memory()?;
+        let start = episode(25, "title").start;
+        let entry = |id: u64| PlaylistEntry {
+            entry_type: "track".to_owned(),
+            id,
+            artist: Some(format!("artist {id}")),
+            title: None,
+            track: None,
+            release: None,
+            time: None,
+            notes: None,
+            content_descriptors: None,
+            approximate_time: None,
+        };
+        archive.record_playlist("black-wax", &start, &[entry(10), entry(20), entry(30)], seen_at(1))?;
+        archive.record_playlist("black-wax", &start, &[entry(30), entry(10)], seen_at(2))?;
+
+        let ids: Vec<u64> = archive.playlist("black-wax", &start)?.iter().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![30, 10]);
+        Ok(())
+    }
+}
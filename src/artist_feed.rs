@@ -0,0 +1,249 @@
+use crate::aggregate::{aggregate_item_builder, program_episodes};
+use crate::airnet::PlaylistCache;
+use crate::airnet::types::{Episode, PlaylistEntry, ProgramDetails};
+use crate::rss::{ChannelBuilder, Item, Rss};
+use chrono::{NaiveTime, TimeDelta};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Output directory of the artist feeds, e.g. `pbsfm/artists/paul-grabowsky/rss.xml`.
+pub const ARTIST_FEEDS_DIR: &str = "artists";
+
+/// Artist credits that name no one in particular and are left out of the top-N.
+const ANONYMOUS_ARTISTS: [&str; 1] = ["various artists"];
+
+/// Feeds of the episodes in which an artist was played.
+///
+/// ```toml
+/// [artist_feeds]
+/// artists = ["Paul Grabowsky"]
+/// top = 10
+/// programs = ["black-wax"]
+/// ```
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ArtistFeeds {
+    #[serde(default)]
+    pub artists: Vec<String>,
+    /// Also generate feeds for this many of the most played artists.
+    pub top: Option<usize>,
+    /// Program slugs whose playlists are searched; every processed program when empty.
+    #[serde(default)]
+    pub programs: Vec<String>,
+}
+
+impl ArtistFeeds {
+    pub fn includes_program(&self, slug: &str) -> bool {
+        self.programs.is_empty() || self.programs.iter().any(|program| program == slug)
+    }
+
+    /// The configured artists followed by the `top` most played artists not already configured.
+    pub fn artists(&self, programs: &[(ProgramDetails, Vec<Episode>)], playlists: &PlaylistCache) -> Vec<String> {
+        let mut artists = self.artists.clone();
+        if let Some(top) = self.top {
+            // Plays are counted per normalized name, under the spelling first seen.
+            let mut plays: HashMap<String, (&str, usize)> = HashMap::new();
+            for (_, playlist) in self.playlists(programs, playlists) {
+                for artist in playlist.iter().filter(|entry| entry.is_track()).filter_map(|entry| entry.artist.as_deref()) {
+                    let artist = artist.trim();
+                    let name = normalize(artist);
+                    if !name.is_empty() && !ANONYMOUS_ARTISTS.contains(&name.as_str()) {
+                        plays.entry(name).or_insert((artist, 0)).1 += 1;
+                    }
+                }
+            }
+            let configured: Vec<String> = self.artists.iter().map(|artist| normalize(artist)).collect();
+            let mut plays: Vec<(String, (&str, usize))> =
+                plays.into_iter().filter(|(name, _)| !configured.contains(name)).collect();
+            plays.sort_by(|(a, (_, a_plays)), (b, (_, b_plays))| b_plays.cmp(a_plays).then(a.cmp(b)));
+            artists.extend(plays.into_iter().map(|(_, (artist, _))| artist.to_owned()).take(top));
+        }
+        artists
+    }
+
+    /// Builds the feed of episodes with at least one track credited to `artist`.
+    /// Each item description lists those tracks and when they were played.
+    pub fn generate(&self, artist: &str, programs: &[(ProgramDetails, Vec<Episode>)], playlists: &PlaylistCache) -> Rss {
+        let credit = artist_credit_regex(artist);
+        let mut episodes: Vec<(&ProgramDetails, &Episode, Vec<&PlaylistEntry>)> = self
+            .playlists(programs, playlists)
+            .into_iter()
+            .filter_map(|((program, episode), playlist)| {
+                let tracks: Vec<&PlaylistEntry> = playlist
+                    .iter()
+                    .filter(|entry| entry.is_track())
+                    .filter(|entry| entry.artist.as_deref().is_some_and(|name| credit.is_match(name)))
+                    .collect();
+                (!tracks.is_empty()).then_some((program, episode, tracks))
+            })
+            .collect();
+        episodes.sort_by_key(|(_, episode, _)| std::cmp::Reverse(episode.start));
+
+        let items: Vec<Item> = episodes
+            .iter()
+            .map(|(program, episode, tracks)| {
+                aggregate_item_builder(program, episode)
+                    .description(tracks_description(artist, episode, tracks))
+                    .build()
+            })
+            .collect();
+
+        Rss::new(
+            ChannelBuilder::new(
+                format!("PBS 106.7FM - {artist}"),
+                "https://www.pbsfm.org.au",
+                format!("PBS 106.7FM episodes featuring {artist}"),
+            )
+            .language("en".to_owned())
+            .item(items)
+            .build(),
+        )
+    }
+
+    fn playlists<'a>(
+        &self,
+        programs: &'a [(ProgramDetails, Vec<Episode>)],
+        playlists: &'a PlaylistCache,
+    ) -> Vec<((&'a ProgramDetails, &'a Episode), &'a [PlaylistEntry])> {
+        program_episodes(programs)
+            .into_iter()
+            .filter(|(program, _)| self.includes_program(&program.slug))
+            .filter_map(|(program, episode)| {
+                playlists
+                    .get(&program.slug, &episode.start)
+                    .map(|playlist| ((program, episode), playlist))
+            })
+            .collect()
+    }
+}
+
+/// The name artist credits are compared by, ignoring case and surrounding whitespace.
+fn normalize(artist: &str) -> String {
+    artist.trim().to_lowercase()
+}
+
+/// Matches credits naming the artist, including collaborations such as
+/// "Paul Grabowsky and Vince Jones". The name is bounded by non-word
+/// characters rather than `\b`, which never matches after a name such as "Sunn O)))".
+fn artist_credit_regex(artist: &str) -> Regex {
+    RegexBuilder::new(&format!(r"(?:^|\W){}(?:$|\W)", regex::escape(artist.trim())))
+        .case_insensitive(true)
+        .build()
+        .expect("Escaped artist name is a valid regex.")
+}
+
+fn tracks_description(artist: &str, episode: &Episode, tracks: &[&PlaylistEntry]) -> String {
+    let lines: Vec<String> = tracks
+        .iter()
+        .map(|entry| {
+            let mut line = format!(
+                "{} - {}",
+                entry.artist.as_deref().unwrap_or(artist),
+                entry.track.as_deref().or(entry.title.as_deref()).unwrap_or("Unknown track"),
+            );
+            if let Some(release) = &entry.release {
+                line.push_str(&format!(" ({release})"));
+            }
+            if let Some(offset) = track_offset(episode, entry) {
+                line.push_str(&format!(" at {}", format_offset(offset)));
+            }
+            line
+        })
+        .collect();
+    format!("Tracks by {artist} in this episode:\n{}", lines.join("\n"))
+}
+
+/// How far into the episode the track was played, from the playlist's
+/// `approximateTime` or, failing that, its time of day.
+pub fn track_offset(episode: &Episode, entry: &PlaylistEntry) -> Option<TimeDelta> {
+    let played = match entry.approximate_time {
+        Some(approximate_time) => approximate_time,
+        None => {
+            let time = NaiveTime::parse_from_str(entry.time.as_deref()?, "%H:%M:%S").ok()?;
+            let played = episode.start.date().and_time(time);
+            // Shows running past midnight list times on the following day.
+            if played < episode.start { played + TimeDelta::days(1) } else { played }
+        }
+    };
+    let offset = played - episode.start;
+    (offset >= TimeDelta::zero() && played <= episode.end).then_some(offset)
+}
+
+pub fn format_offset(offset: TimeDelta) -> String {
+    let seconds = offset.num_seconds();
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn episode() -> Episode {
+        test_support::episode(test_support::datetime(8, 25, 23))
+    }
+
+    fn track(time: Option<&str>) -> PlaylistEntry {
+        PlaylistEntry {
+            entry_type: "track".to_owned(),
+            id: 1,
+            artist: Some("Paul Grabowsky and Vince Jones".to_owned()),
+            title: Some("This Is Always".to_owned()),
+            track: Some("This Is Always".to_owned()),
+            release: Some("Provenance".to_owned()),
+            time: time.map(str::to_owned),
+            notes: None,
+            content_descriptors: None,
+            approximate_time: None,
+        }
+    }
+
+    #[test]
+    fn test_top_artists_ignore_case() {
+        let feeds = ArtistFeeds { top: Some(1), ..Default::default() };
+        let program = test_support::program("black-wax");
+        let episodes = vec![episode()];
+        let mut playlists = PlaylistCache::default();
+        let credits = ["Vince Jones", "Vince Jones", "Paul Grabowsky", "PAUL GRABOWSKY", " paul grabowsky"];
+        let playlist = credits
+            .iter()
+            .map(|artist| PlaylistEntry { artist: Some((*artist).to_owned()), ..track(None) })
+            .collect();
+        playlists.insert("black-wax", &episodes[0].start, playlist);
+
+        assert_eq!(feeds.artists(&[(program, episodes)], &playlists), vec!["Paul Grabowsky"]);
+    }
+
+    #[test]
+    fn test_track_offset_across_midnight() {
+        let offset = track_offset(&episode(), &track(Some("00:42:05"))).unwrap();
+        assert_eq!(format_offset(offset), "1:42:05");
+    }
+
+    #[test]
+    fn test_track_offset_unknown() {
+        assert_eq!(track_offset(&episode(), &track(None)), None);
+        assert_eq!(track_offset(&episode(), &track(Some("12:00:00"))), None);
+    }
+
+    #[test]
+    fn test_artist_credit() {
+        let credit = artist_credit_regex("paul grabowsky");
+        assert!(credit.is_match("Paul Grabowsky and Vince Jones"));
+        assert!(!credit.is_match("Paul Grabowskyish"));
+        let credit = artist_credit_regex("Sunn O)))");
+        assert!(credit.is_match("Sunn O)))"));
+        assert!(credit.is_match("Boris with Sunn O)))"));
+        assert!(!credit.is_match("Sunn O)))x"));
+    }
+
+    #[test]
+    fn test_tracks_description() {
+        let entry = track(Some("23:05:00"));
+        assert_eq!(
+            tracks_description("Paul Grabowsky", &episode(), &[&entry]),
+            "Tracks by Paul Grabowsky in this episode:\n\
+            Paul Grabowsky and Vince Jones - This Is Always (Provenance) at 0:05:00"
+        );
+    }
+}
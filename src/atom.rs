@@ -0,0 +1,217 @@
+use crate::rss::{Item, Rss};
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use chrono_tz::Australia::Melbourne;
+use quick_xml::events::{BytesDecl, Event};
+use quick_xml::writer::Writer;
+use serde::Serialize;
+use std::io::Write;
+
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+
+/// An Atom feed with the same entries as an RSS feed.
+#[serde_with::apply(
+    Option => #[serde(skip_serializing_if = "Option::is_none")],
+)]
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename = "feed")]
+pub struct AtomFeed {
+    #[serde(rename = "@xmlns")]
+    xmlns: &'static str,
+    title: String,
+    subtitle: Option<String>,
+    id: String,
+    updated: String,
+    link: Vec<AtomLink>,
+    icon: Option<String>,
+    entry: Vec<AtomEntry>,
+}
+
+#[serde_with::apply(
+    Option => #[serde(skip_serializing_if = "Option::is_none")],
+)]
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AtomLink {
+    #[serde(rename = "@href")]
+    href: String,
+    #[serde(rename = "@rel")]
+    rel: Option<String>,
+    #[serde(rename = "@type")]
+    media_type: Option<String>,
+    #[serde(rename = "@length")]
+    length: Option<u64>,
+}
+
+impl AtomLink {
+    pub fn new<H: Into<String>>(href: H, rel: Option<&str>, media_type: Option<&str>) -> Self {
+        AtomLink {
+            href: href.into(),
+            rel: rel.map(str::to_owned),
+            media_type: media_type.map(str::to_owned),
+            length: None,
+        }
+    }
+}
+
+#[serde_with::apply(
+    Option => #[serde(skip_serializing_if = "Option::is_none")],
+)]
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AtomEntry {
+    title: String,
+    id: String,
+    updated: String,
+    link: Vec<AtomLink>,
+    author: Option<AtomPerson>,
+    summary: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AtomPerson {
+    name: String,
+}
+
+impl AtomFeed {
+    /// Converts the RSS feed. Atom's `updated` time is taken from the newest
+    /// entry, or the channel's `lastBuildDate` or `pubDate` when there are
+    /// none, so it only changes when the feed does. A feed without any date
+    /// is updated `now`, as Atom requires the time. Entries without a
+    /// `pubDate` share the feed's time.
+    pub fn from_rss(rss: &Rss, now: DateTime<Utc>) -> Self {
+        let channel = rss.channel();
+        let updated = channel
+            .items()
+            .iter()
+            .filter_map(item_date)
+            .max()
+            .or_else(|| channel.last_build_date().and_then(parse_date))
+            .or_else(|| channel.pub_date().and_then(parse_date))
+            .unwrap_or_else(|| now.with_timezone(&Melbourne).fixed_offset());
+        let updated = atom_date(updated);
+        let entry: Vec<AtomEntry> = channel.items().iter().map(|item| AtomEntry::from_item(item, &updated)).collect();
+
+        AtomFeed {
+            xmlns: ATOM_NAMESPACE,
+            title: channel.title().to_owned(),
+            subtitle: Some(channel.description().to_owned()).filter(|description| !description.is_empty()),
+            id: channel.link().to_owned(),
+            updated,
+            link: vec![AtomLink::new(channel.link(), Some("alternate"), Some("text/html"))],
+            icon: channel
+                .itunes_image()
+                .map(|artwork| artwork.href().to_owned())
+                .or(channel.image().map(|image| image.url().to_owned())),
+            entry,
+        }
+    }
+
+    /// Adds a link to the feed itself, or another representation of it.
+    pub fn add_link(&mut self, link: AtomLink) {
+        self.link.push(link);
+    }
+
+    pub fn write<W: Write>(&self, out: W) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = Writer::new_with_indent(out, b' ', 2);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+        writer.write_serializable("feed", self)?;
+        Ok(())
+    }
+}
+
+impl AtomEntry {
+    fn from_item(item: &Item, feed_updated: &str) -> Self {
+        let mut link = Vec::new();
+        if let Some(href) = item.link() {
+            link.push(AtomLink::new(href, Some("alternate"), Some("text/html")));
+        }
+        if let Some(enclosure) = item.enclosure() {
+            link.push(AtomLink {
+                length: enclosure.length().filter(|length| *length > 0),
+                ..AtomLink::new(enclosure.url(), Some("enclosure"), Some(enclosure.media_type()))
+            });
+        }
+        AtomEntry {
+            title: item.title().unwrap_or_default().to_owned(),
+            id: item
+                .guid()
+                .map(|guid| guid.value())
+                .or(item.link())
+                .unwrap_or_default()
+                .to_owned(),
+            updated: item_date(item).map_or_else(|| feed_updated.to_owned(), atom_date),
+            link,
+            author: item.author().map(|name| AtomPerson { name: name.to_owned() }),
+            summary: item.description().map(str::to_owned),
+        }
+    }
+}
+
+pub(crate) fn item_date(item: &Item) -> Option<DateTime<FixedOffset>> {
+    parse_date(item.pub_date()?)
+}
+
+fn parse_date(date: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc2822(date).ok()
+}
+
+fn atom_date(date: DateTime<FixedOffset>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::{ChannelBuilder, Enclosure, ItemBuilder, ItemGuidBuilder};
+    use chrono::TimeZone;
+    use quick_xml::se::to_string;
+
+    #[test]
+    fn test_from_rss() {
+        let rss = Rss::new(
+            ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "")
+                .item(vec![
+                    ItemBuilder::with_title("Episode")
+                        .link("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00".to_owned())
+                        .guid(ItemGuidBuilder::new("https://airnet/episodes/1").is_permalink(false).build())
+                        .author("Adam Rudegeair".to_owned())
+                        .enclosure(Enclosure::new("https://airnet/audio.m4a", Some(0), "audio/mp4"))
+                        .pub_date("Mon, 16 Jun 2025 11:00:00 +1000".to_owned())
+                        .build(),
+                ])
+                .build(),
+        );
+        assert_eq!(
+            to_string(&AtomFeed::from_rss(&rss, Utc::now())).unwrap(),
+            "<feed xmlns=\"http://www.w3.org/2005/Atom\">\
+                <title>Black Wax</title>\
+                <id>https://www.pbsfm.org.au/program/black-wax</id>\
+                <updated>2025-06-16T11:00:00+10:00</updated>\
+                <link href=\"https://www.pbsfm.org.au/program/black-wax\" rel=\"alternate\" type=\"text/html\"/>\
+                <entry>\
+                    <title>Episode</title>\
+                    <id>https://airnet/episodes/1</id>\
+                    <updated>2025-06-16T11:00:00+10:00</updated>\
+                    <link href=\"https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00\" rel=\"alternate\" type=\"text/html\"/>\
+                    <link href=\"https://airnet/audio.m4a\" rel=\"enclosure\" type=\"audio/mp4\"/>\
+                    <author><name>Adam Rudegeair</name></author>\
+                </entry>\
+            </feed>"
+        );
+    }
+
+    #[test]
+    fn test_from_rss_without_dates() {
+        let rss = Rss::new(
+            ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "")
+                .last_build_date("Mon, 1 Sep 2025 12:00:00 +1000".to_owned())
+                .item(vec![ItemBuilder::with_title("Black Wax has ended").build()])
+                .build(),
+        );
+        let now = Utc.with_ymd_and_hms(2025, 9, 2, 2, 0, 0).unwrap();
+        let feed = AtomFeed::from_rss(&rss, now);
+        assert_eq!(feed.updated, "2025-09-01T12:00:00+10:00");
+        assert_eq!(feed.entry[0].updated, feed.updated);
+
+        let rss = Rss::new(ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "").build());
+        assert_eq!(AtomFeed::from_rss(&rss, now).updated, "2025-09-02T12:00:00+10:00");
+    }
+}
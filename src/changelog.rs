@@ -0,0 +1,297 @@
+use crate::airnet::types::ProgramDescription;
+use crate::rss::{ChannelBuilder, Item, ItemBuilder, ItemGuidBuilder, Rss};
+use crate::slugify;
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono_tz::Australia::Melbourne;
+use std::path::Path;
+
+/// Output directory of the changelog feed, alongside the per-program directories.
+pub const CHANGELOG_SLUG: &str = "changelog";
+
+/// File, next to the changelog feed, holding the program list it was last compared against.
+pub const SNAPSHOT_FILE: &str = "all-programs.json";
+
+/// How many changes the feed keeps, newest first.
+const MAX_ITEMS: usize = 100;
+
+/// A difference in the station's program list between two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgramChange {
+    /// The program as it is now, or as it last was when it has been removed.
+    pub program: ProgramDescription,
+    /// The program's slug, which archived programs no longer have, as it was before the change.
+    pub previous_slug: Option<String>,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Archived,
+    Unarchived,
+    Renamed { old_name: String },
+    BroadcastersChanged { old_broadcasters: String },
+    GridDescriptionChanged { old_grid_description: Option<String> },
+}
+
+/// The changes from the `previous` program list to the `current` one.
+///
+/// Programs are matched by slug, then by name, as Airnet drops the slug of
+/// archived programs. A program renamed along with its slug can't be told
+/// apart from one program removed and another added, and is reported as such.
+pub fn diff(previous: &[ProgramDescription], current: &[ProgramDescription]) -> Vec<ProgramChange> {
+    let mut unmatched: Vec<&ProgramDescription> = previous.iter().collect();
+    let mut matched = Vec::new();
+    let mut added = Vec::new();
+    for program in current {
+        let position = unmatched
+            .iter()
+            .position(|old| old.slug.is_some() && old.slug == program.slug)
+            .or_else(|| unmatched.iter().position(|old| old.name == program.name));
+        match position {
+            Some(position) => matched.push((unmatched.remove(position), program)),
+            None => added.push(program),
+        }
+    }
+
+    let mut changes = Vec::new();
+    let change = |program: &ProgramDescription, previous_slug: &Option<String>, kind| ProgramChange {
+        program: program.clone(),
+        previous_slug: previous_slug.clone(),
+        kind,
+    };
+    for program in added {
+        changes.push(change(program, &None, ChangeKind::Added));
+    }
+    for (old, program) in matched {
+        if old.name != program.name {
+            changes.push(change(program, &old.slug, ChangeKind::Renamed { old_name: old.name.clone() }));
+        }
+        match (old.archived, program.archived) {
+            (false, true) => changes.push(change(program, &old.slug, ChangeKind::Archived)),
+            (true, false) => changes.push(change(program, &old.slug, ChangeKind::Unarchived)),
+            _ => {}
+        }
+        // Airnet blanks the presenters of archived programs, which isn't news.
+        if old.broadcasters != program.broadcasters && !program.archived {
+            let kind = ChangeKind::BroadcastersChanged { old_broadcasters: old.broadcasters.clone() };
+            changes.push(change(program, &old.slug, kind));
+        }
+        if old.grid_description != program.grid_description {
+            let kind = ChangeKind::GridDescriptionChanged { old_grid_description: old.grid_description.clone() };
+            changes.push(change(program, &old.slug, kind));
+        }
+    }
+    for old in unmatched {
+        changes.push(change(old, &old.slug, ChangeKind::Removed));
+    }
+    changes
+}
+
+impl ProgramChange {
+    fn kind_name(&self) -> &'static str {
+        match self.kind {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Archived => "archived",
+            ChangeKind::Unarchived => "unarchived",
+            ChangeKind::Renamed { .. } => "renamed",
+            ChangeKind::BroadcastersChanged { .. } => "broadcasters",
+            ChangeKind::GridDescriptionChanged { .. } => "grid-description",
+        }
+    }
+
+    pub fn title(&self) -> String {
+        let name = &self.program.name;
+        match &self.kind {
+            ChangeKind::Added => format!("New program: {name}"),
+            ChangeKind::Removed => format!("Program removed: {name}"),
+            ChangeKind::Archived => format!("Program archived: {name}"),
+            ChangeKind::Unarchived => format!("Program back on air: {name}"),
+            ChangeKind::Renamed { old_name } => format!("Program renamed: {old_name} is now {name}"),
+            ChangeKind::BroadcastersChanged { .. } => format!("New presenters for {name}"),
+            ChangeKind::GridDescriptionChanged { .. } => format!("New description for {name}"),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        let program = &self.program;
+        let name = &program.name;
+        match &self.kind {
+            ChangeKind::Added => {
+                let mut description = format!("{name} is a new program on PBS 106.7FM");
+                if !program.broadcasters.is_empty() {
+                    description.push_str(&format!(", presented by {}", program.broadcasters));
+                }
+                if let Some(grid_description) = program.grid_description.as_deref().filter(|text| !text.is_empty()) {
+                    description.push_str(&format!(": {grid_description}"));
+                }
+                description + "."
+            }
+            ChangeKind::Removed => format!("{name} is no longer listed on Airnet."),
+            ChangeKind::Archived => format!("{name} has been archived and will not air new episodes."),
+            ChangeKind::Unarchived => format!("{name} is no longer archived."),
+            ChangeKind::Renamed { old_name } => format!("{old_name} is now called {name}."),
+            ChangeKind::BroadcastersChanged { old_broadcasters } => {
+                format!("{name} is now presented by {}, previously {old_broadcasters}.", program.broadcasters)
+            }
+            ChangeKind::GridDescriptionChanged { old_grid_description } => format!(
+                "{name} is now described as \"{}\", previously \"{}\".",
+                program.grid_description.as_deref().unwrap_or_default(),
+                old_grid_description.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+
+    /// The changelog item, linking to the program's page. The link carries
+    /// a fragment unique to the change, as published guids are kept by link.
+    pub fn item(&self, changed_at: DateTime<Utc>) -> Item {
+        let page = match self.program.slug.as_ref().or(self.previous_slug.as_ref()) {
+            Some(slug) => format!("https://www.pbsfm.org.au/program/{slug}"),
+            None => "https://www.pbsfm.org.au".to_owned(),
+        };
+        let link = format!(
+            "{page}#{}-{}-{}",
+            self.kind_name(),
+            slugify(&self.program.name),
+            changed_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+        );
+        ItemBuilder::with_title(self.title())
+            .description(self.description())
+            .link(link.clone())
+            .guid(ItemGuidBuilder::new(link).is_permalink(false).build())
+            .pub_date(changed_at.with_timezone(&Melbourne).to_rfc2822())
+            .build()
+    }
+}
+
+/// The changelog feed: the `changes` found at `changed_at`, followed by the
+/// items of the `previous` feed, up to `MAX_ITEMS`.
+pub fn changelog_feed(previous: Option<&Rss>, changes: &[ProgramChange], changed_at: DateTime<Utc>) -> Rss {
+    let items: Vec<Item> = changes
+        .iter()
+        .map(|change| change.item(changed_at))
+        .chain(previous.into_iter().flat_map(|rss| rss.channel().items().iter().cloned()))
+        .take(MAX_ITEMS)
+        .collect();
+    Rss::new(
+        ChannelBuilder::new(
+            "PBS 106.7FM - Program changes",
+            "https://www.pbsfm.org.au",
+            "New, archived and renamed programs, and changes to their presenters and descriptions, on PBS 106.7FM",
+        )
+        .language("en".to_owned())
+        .item(items)
+        .build(),
+    )
+}
+
+/// Reads the program list saved by the last run, if there was one.
+pub fn read_snapshot(path: &Path) -> Result<Option<Vec<ProgramDescription>>, Box<dyn std::error::Error>> {
+    match std::fs::read(path) {
+        Ok(snapshot) => Ok(Some(serde_json::from_slice(&snapshot)?)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fixture_changes(before: &str, after: &str) -> Vec<(String, ChangeKind)> {
+        let before: Vec<ProgramDescription> = serde_json::from_str(before).unwrap();
+        let after: Vec<ProgramDescription> = serde_json::from_str(after).unwrap();
+        diff(&before, &after).into_iter().map(|change| (change.program.name, change.kind)).collect()
+    }
+
+    #[test]
+    fn test_diff_new_and_removed() {
+        let changes = fixture_changes(
+            include_str!("../tests/changelog/new-and-removed.before.json"),
+            include_str!("../tests/changelog/new-and-removed.after.json"),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                ("The Breakfast Spread".to_owned(), ChangeKind::Added),
+                ("Soul Time".to_owned(), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_archived() {
+        let changes = fixture_changes(
+            include_str!("../tests/changelog/archived.before.json"),
+            include_str!("../tests/changelog/archived.after.json"),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                ("Tomorrow Land".to_owned(), ChangeKind::Archived),
+                ("Mojo Juju".to_owned(), ChangeKind::Unarchived),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_renamed_and_changed() {
+        let changes = fixture_changes(
+            include_str!("../tests/changelog/renamed-and-changed.before.json"),
+            include_str!("../tests/changelog/renamed-and-changed.after.json"),
+        );
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "Black Wax".to_owned(),
+                    ChangeKind::BroadcastersChanged { old_broadcasters: "Adam Rudegeair".to_owned() }
+                ),
+                (
+                    "Black Wax".to_owned(),
+                    ChangeKind::GridDescriptionChanged { old_grid_description: Some("Groovin' jazz".to_owned()) }
+                ),
+                ("Soul Time Revue".to_owned(), ChangeKind::Renamed { old_name: "Soul Time".to_owned() }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let programs: Vec<ProgramDescription> =
+            serde_json::from_str(include_str!("../tests/changelog/archived.after.json")).unwrap();
+        assert!(diff(&programs, &programs).is_empty());
+    }
+
+    #[test]
+    fn test_changelog_feed() {
+        let before: Vec<ProgramDescription> =
+            serde_json::from_str(include_str!("../tests/changelog/archived.before.json")).unwrap();
+        let after: Vec<ProgramDescription> =
+            serde_json::from_str(include_str!("../tests/changelog/archived.after.json")).unwrap();
+        let first_run = Utc.with_ymd_and_hms(2025, 9, 1, 2, 0, 0).unwrap();
+        let previous = changelog_feed(None, &diff(&before, &after), first_run);
+        let rss = changelog_feed(Some(&previous), &diff(&after, &before), first_run + chrono::TimeDelta::days(1));
+
+        let titles: Vec<&str> = rss.channel().items().iter().filter_map(Item::title).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Program back on air: Tomorrow Land",
+                "New presenters for Tomorrow Land",
+                "Program archived: Mojo Juju",
+                "Program archived: Tomorrow Land",
+                "Program back on air: Mojo Juju",
+            ]
+        );
+        let archived = &rss.channel().items()[3];
+        assert_eq!(
+            archived.link(),
+            Some("https://www.pbsfm.org.au/program/tomorrow-land#archived-tomorrow-land-2025-09-01T02:00:00Z")
+        );
+        assert_eq!(archived.pub_date(), Some("Mon, 1 Sep 2025 12:00:00 +1000"));
+    }
+}
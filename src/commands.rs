@@ -0,0 +1,195 @@
+use crate::airnet::{AirnetClient, PlaylistCache};
+use crate::airnet::types::{Episode, ProgramDescription, ProgramDetails};
+use crate::live;
+use crate::search::OutputFormat;
+use crate::stats::{self, ProgramStats, ReportFormat};
+use crate::validation;
+use crate::{PBSFM_STATION, episode_title, rm_query_params};
+use chrono::{DateTime, Utc};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+#[derive(clap::Args, Debug)]
+pub struct ListProgramsArgs {
+    #[arg(short, long, default_value = "https://airnet.org.au")]
+    pub airnet_url: String,
+
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ShowArgs {
+    /// Program slug, e.g. `black-wax`
+    pub slug: String,
+
+    #[arg(short, long, default_value = "https://airnet.org.au")]
+    pub airnet_url: String,
+
+    /// Number of most recent episodes to list
+    #[arg(short, long, default_value_t = 5)]
+    pub episodes: usize,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct StatsArgs {
+    #[arg(short, long, value_parser, num_args = 1.., value_delimiter = ',', required = true)]
+    pub programs: Vec<String>,
+
+    #[arg(short, long, default_value = "https://airnet.org.au")]
+    pub airnet_url: String,
+
+    #[arg(short, long, value_enum, default_value_t = ReportFormat::Json)]
+    pub format: ReportFormat,
+
+    /// File to write the report to instead of printing it
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ValidateArgs {
+    /// Feed to check, e.g. `docs/feeds/pbsfm/black-wax/rss.xml`
+    pub file: PathBuf,
+}
+
+pub fn run_list_programs(args: ListProgramsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let client = AirnetClient::new(args.airnet_url);
+    let programs = client.all_programs(PBSFM_STATION)?;
+    let stdout = std::io::stdout();
+    write_programs(stdout.lock(), &programs, args.format)
+}
+
+/// Lists every program Airnet knows about, one per line, or as JSON.
+pub fn write_programs<W: Write>(
+    mut writer: W,
+    programs: &[ProgramDescription],
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Json => {
+            let programs: Vec<serde_json::Value> = programs
+                .iter()
+                .map(|program| {
+                    serde_json::json!({
+                        "slug": program.slug,
+                        "name": program.name,
+                        "archived": program.archived,
+                        "broadcasters": program.broadcasters,
+                    })
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut writer, &programs)?;
+            writeln!(writer)?;
+        }
+        OutputFormat::Table => {
+            let width = programs
+                .iter()
+                .filter_map(|program| program.slug.as_deref())
+                .map(|slug| slug.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max("Slug".len());
+            writeln!(writer, "{:width$}  Archived  Broadcasters", "Slug")?;
+            for program in programs {
+                let line = format!(
+                    "{:width$}  {:8}  {}",
+                    program.slug.as_deref().unwrap_or("-"),
+                    if program.archived { "yes" } else { "no" },
+                    program.broadcasters,
+                );
+                writeln!(writer, "{}", line.trim_end())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run_show(args: ShowArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let client = AirnetClient::new(args.airnet_url);
+    let program = client.program(PBSFM_STATION, &args.slug)?;
+    let episodes = client.episodes(PBSFM_STATION, &args.slug)?;
+    let stdout = std::io::stdout();
+    write_program(stdout.lock(), &program, &episodes, args.episodes)
+}
+
+/// Prints a program's details followed by its `count` most recent episodes.
+pub fn write_program<W: Write>(
+    mut writer: W,
+    program: &ProgramDetails,
+    episodes: &[Episode],
+    count: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    writeln!(writer, "{} ({})", program.name, program.slug)?;
+    writeln!(writer, "Broadcasters: {}", program.broadcasters)?;
+    if let Some(grid_description) = &program.grid_description {
+        writeln!(writer, "Grid description: {grid_description}")?;
+    }
+    writeln!(writer, "Image: {}", rm_query_params(&program.profile_image_url)?)?;
+    writeln!(writer)?;
+    writeln!(writer, "{}", program.description.trim())?;
+
+    let mut recent: Vec<&Episode> = episodes.iter().collect();
+    recent.sort_by_key(|episode| std::cmp::Reverse(episode.start));
+    writeln!(writer)?;
+    writeln!(writer, "Recent episodes:")?;
+    for episode in recent.into_iter().take(count) {
+        writeln!(
+            writer,
+            "  {}  {}",
+            episode.start.format("%Y-%m-%d %H:%M"),
+            episode_title(episode)
+        )?;
+    }
+    Ok(())
+}
+
+pub fn run_stats(args: StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let client = AirnetClient::new(args.airnet_url);
+    let programs = fetch_program_stats(&client, &mut PlaylistCache::default(), &args.programs, Utc::now())?;
+    match &args.output {
+        Some(path) => stats::write_report(BufWriter::new(File::create(path)?), &programs, args.format),
+        None => {
+            let stdout = std::io::stdout();
+            stats::write_report(stdout.lock(), &programs, args.format)?;
+            // Unlike the CSV, the JSON report doesn't end with a newline.
+            if args.format == ReportFormat::Json {
+                println!();
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The content quota statistics of each program's episodes that have aired
+/// by `now`, fetching only the playlists not already in `playlists`.
+pub fn fetch_program_stats(
+    client: &AirnetClient,
+    playlists: &mut PlaylistCache,
+    slugs: &[String],
+    now: DateTime<Utc>,
+) -> Result<Vec<ProgramStats>, Box<dyn std::error::Error>> {
+    let mut programs = Vec::with_capacity(slugs.len());
+    for slug in slugs {
+        let program = client.program(PBSFM_STATION, slug)?;
+        let episodes = live::aired_episodes(client.episodes(PBSFM_STATION, slug)?, now);
+        programs.push(ProgramStats::fetch(client, playlists, PBSFM_STATION, &program, &episodes)?);
+    }
+    Ok(programs)
+}
+
+/// Prints the feed's validation issues, including those of its downloaded
+/// artwork, failing if any of them is an error.
+pub fn run_validate(args: ValidateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let rss = validation::read_feed(&args.file)?;
+    let issues = validation::validate_with_artwork(&rss, &mut validation::ArtworkChecker::default());
+    for issue in &issues {
+        println!("{}: {issue}", args.file.display());
+    }
+    if validation::has_errors(&issues) {
+        return Err(format!("{} failed validation", args.file.display()).into());
+    }
+    println!("{}: OK", args.file.display());
+    Ok(())
+}
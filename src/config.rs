@@ -0,0 +1,125 @@
+use crate::artist_feed::ArtistFeeds;
+use crate::genre::{DEFAULT_GENRE_DOMAIN, Genre};
+use crate::smart_feed::SmartFeed;
+use crate::webhook::Webhook;
+use crate::is_slug;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Optional settings read from a TOML file given with `--config`, for
+/// outputs that don't fit on the command line.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Config {
+    #[serde(default)]
+    pub smart_feeds: Vec<SmartFeed>,
+    pub artist_feeds: Option<ArtistFeeds>,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+    /// Old program slugs mapped to the slugs they were renamed to or merged
+    /// into, each old slug keeping a stub feed pointing to the new one.
+    ///
+    /// ```toml
+    /// [redirects]
+    /// old-slug = "new-slug"
+    /// ```
+    #[serde(default)]
+    pub redirects: BTreeMap<String, String>,
+    #[serde(default)]
+    pub genres: Vec<Genre>,
+    /// Identifies the genre taxonomy in the `domain` of the genre categories.
+    pub genre_domain: Option<String>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        let config: Config = toml::from_str(&contents)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the smart feeds and genres have distinct slugs that are safe as
+    /// paths, the genres have Apple Podcasts categories, and the redirects are
+    /// between such slugs.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut smart_feed_slugs = HashSet::new();
+        for smart_feed in &self.smart_feeds {
+            if !is_slug(&smart_feed.slug) {
+                return Err(format!(
+                    "smart feed {:?}: slug must be lower-case letters, digits and hyphens",
+                    smart_feed.slug
+                ));
+            }
+            if !smart_feed_slugs.insert(smart_feed.slug.as_str()) {
+                return Err(format!("smart feed {}: slug is used by more than one smart feed", smart_feed.slug));
+            }
+        }
+        for (old_slug, new_slug) in &self.redirects {
+            if !is_slug(old_slug) || !is_slug(new_slug) {
+                return Err(format!(
+                    "redirect {old_slug:?} = {new_slug:?}: slugs must be lower-case letters, digits and hyphens"
+                ));
+            }
+            if old_slug == new_slug {
+                return Err(format!("redirect {old_slug}: redirects to itself"));
+            }
+        }
+        let mut slugs = HashSet::new();
+        for genre in &self.genres {
+            if !is_slug(&genre.slug) {
+                return Err(format!("genre {:?}: slug must be lower-case letters, digits and hyphens", genre.slug));
+            }
+            if !slugs.insert(genre.slug.as_str()) {
+                return Err(format!("genre {}: slug is used by more than one genre", genre.slug));
+            }
+            genre.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn genre_domain(&self) -> &str {
+        self.genre_domain.as_deref().unwrap_or(DEFAULT_GENRE_DOMAIN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genre(slug: &str) -> String {
+        format!("[[genres]]\nslug = {slug:?}\nname = \"Jazz\"\napple_category = \"Music\"\n")
+    }
+
+    fn validate(toml: &str) -> Result<(), String> {
+        toml::from_str::<Config>(toml).unwrap().validate()
+    }
+
+    #[test]
+    fn test_validate_genres() {
+        assert!(validate(&[genre("jazz"), genre("soul-and-funk")].concat()).is_ok());
+        assert!(validate(&[genre("jazz"), genre("jazz")].concat()).is_err());
+        assert!(validate(&genre("../jazz")).is_err());
+        assert!(validate(&genre("Jazz")).is_err());
+        assert!(validate(&genre("")).is_err());
+    }
+
+    #[test]
+    fn test_validate_smart_feeds() {
+        let smart_feed = |slug: &str| {
+            format!("[[smart_feeds]]\nname = \"Interviews\"\nslug = {slug:?}\nfilter = \"title ~ /interview/i\"\n")
+        };
+        assert!(validate(&[smart_feed("interviews"), smart_feed("long-sets")].concat()).is_ok());
+        assert!(validate(&[smart_feed("interviews"), smart_feed("interviews")].concat()).is_err());
+        assert!(validate(&smart_feed("../x")).is_err());
+        assert!(validate(&smart_feed("")).is_err());
+    }
+
+    #[test]
+    fn test_validate_redirects() {
+        assert!(validate("[redirects]\nblack-wax-classic = \"black-wax\"\n").is_ok());
+        assert!(validate("[redirects]\n\"../black-wax\" = \"black-wax\"\n").is_err());
+        assert!(validate("[redirects]\nblack-wax-classic = \"black-wax/rss.xml\"\n").is_err());
+        assert!(validate("[redirects]\nblack-wax = \"black-wax\"\n").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::airnet::AirnetClient;
+use crate::airnet::types::Episode;
+use crate::config::Config;
+use crate::{Args, PBSFM_STATION, generate_feeds, station_datetime};
+use chrono::{DateTime, TimeDelta, Utc};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// How long to wait before trying a program again after its run failed or
+/// another run held the lock.
+const RETRY_DELAY: TimeDelta = TimeDelta::minutes(10);
+
+#[derive(clap::Args, Debug)]
+pub struct DaemonArgs {
+    #[command(flatten)]
+    pub args: Args,
+
+    /// Minutes after an episode ends before its program's feed is refreshed, giving Airnet time to publish the audio
+    #[arg(long, default_value_t = 15, value_parser = clap::value_parser!(u32).range(1..))]
+    pub refresh_delay: u32,
+
+    /// Up to this many seconds are added at random to each refresh, to spread requests to Airnet
+    #[arg(long, default_value_t = 300)]
+    pub jitter: u32,
+
+    /// Hours between full runs, which also write the station-wide outputs
+    #[arg(long, default_value_t = 24, value_parser = clap::value_parser!(u32).range(1..))]
+    pub full_refresh_hours: u32,
+
+    /// File locked during each run so that runs never overlap [default: <OUTPUT_DIR>/.daemon.lock]
+    #[arg(long)]
+    pub lock_file: Option<PathBuf>,
+}
+
+/// Keeps the feeds up to date until SIGTERM or SIGINT: a full run on start
+/// and every `--full-refresh-hours`, and in between each program's feed is
+/// refreshed shortly after its episodes end.
+pub fn run_daemon(daemon_args: DaemonArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] {
+        signal_hook::flag::register(signal, Arc::clone(&shutdown))?;
+    }
+    std::fs::create_dir_all(&daemon_args.args.output_dir)?;
+    let lock_path = daemon_args
+        .lock_file
+        .clone()
+        .unwrap_or_else(|| daemon_args.args.output_dir.join(".daemon.lock"));
+
+    let mut daemon = Daemon::new(daemon_args, lock_path, Arc::clone(&shutdown));
+    while !shutdown.load(Ordering::Relaxed) {
+        daemon.run_due(Utc::now());
+        let wake_at = daemon.next_wake();
+        println!("Next refresh at {}", wake_at.to_rfc3339());
+        sleep_until(wake_at, &shutdown);
+    }
+    println!("Shutting down");
+    Ok(())
+}
+
+struct Daemon {
+    args: DaemonArgs,
+    lock_path: PathBuf,
+    shutdown: Arc<AtomicBool>,
+    next_full_run: DateTime<Utc>,
+    next_refresh: HashMap<String, DateTime<Utc>>,
+}
+
+impl Daemon {
+    fn new(args: DaemonArgs, lock_path: PathBuf, shutdown: Arc<AtomicBool>) -> Self {
+        Daemon { args, lock_path, shutdown, next_full_run: DateTime::<Utc>::MIN_UTC, next_refresh: HashMap::new() }
+    }
+
+    fn shutting_down(&self) -> bool {
+        self.shutdown.load(Ordering::Relaxed)
+    }
+
+    fn run_due(&mut self, now: DateTime<Utc>) {
+        if now >= self.next_full_run {
+            let programs = self.args.args.programs.clone();
+            if self.run(self.args.args.clone(), false, now) {
+                self.next_full_run = now + TimeDelta::hours(self.args.full_refresh_hours.into()) + self.jitter();
+                self.schedule(&programs, now);
+            } else {
+                self.next_full_run = now + RETRY_DELAY;
+            }
+            return;
+        }
+
+        let due: Vec<String> = self
+            .next_refresh
+            .iter()
+            .filter(|(_, refresh_at)| **refresh_at <= now)
+            .map(|(program, _)| program.clone())
+            .collect();
+        if due.is_empty() || self.shutting_down() {
+            return;
+        }
+        if self.run(self.args.args.without_station_outputs(due.clone()), true, now) {
+            self.schedule(&due, now);
+        } else {
+            for program in due {
+                self.next_refresh.insert(program, now + RETRY_DELAY);
+            }
+        }
+    }
+
+    /// Generates feeds with the lock held, returning whether the run succeeded.
+    fn run(&self, args: Args, program_feeds_only: bool, now: DateTime<Utc>) -> bool {
+        let _lock = match RunLock::acquire(&self.lock_path) {
+            Ok(Some(lock)) => lock,
+            Ok(None) => {
+                eprintln!("Another run holds {}, retrying later", self.lock_path.display());
+                return false;
+            }
+            Err(err) => {
+                eprintln!("Could not lock {}: {err}", self.lock_path.display());
+                return false;
+            }
+        };
+        println!("Refreshing feeds: {}", args.programs.join(", "));
+        let config = match &args.config {
+            Some(path) => Config::load(path),
+            None => Ok(Config::default()),
+        };
+        let result = config.and_then(|config| {
+            let config = if program_feeds_only { program_feeds_config(config) } else { config };
+            generate_feeds(args, &config, now)
+        });
+        match result {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("Feed refresh failed: {err}");
+                false
+            }
+        }
+    }
+
+    /// Schedules each program's next refresh from its episodes' air times.
+    fn schedule(&mut self, programs: &[String], now: DateTime<Utc>) {
+        let client = AirnetClient::new(self.args.args.airnet_url.clone());
+        let delay = TimeDelta::minutes(self.args.refresh_delay.into());
+        for program in programs {
+            // Scheduling fetches every program's episodes, so a shutdown needn't wait for all of them.
+            if self.shutting_down() {
+                return;
+            }
+            let refresh_at = match client.episodes(PBSFM_STATION, program) {
+                Ok(episodes) => next_refresh(&episodes, now, delay).unwrap_or(self.next_full_run) + self.jitter(),
+                Err(err) => {
+                    eprintln!("Could not fetch episodes to schedule {program}: {err}");
+                    now + RETRY_DELAY
+                }
+            };
+            println!("Scheduled {program} for {}", refresh_at.to_rfc3339());
+            self.next_refresh.insert(program.clone(), refresh_at);
+        }
+    }
+
+    fn next_wake(&self) -> DateTime<Utc> {
+        self.next_refresh.values().copied().fold(self.next_full_run, DateTime::min)
+    }
+
+    fn jitter(&self) -> TimeDelta {
+        TimeDelta::seconds(fastrand::i64(0..=self.args.jitter.into()))
+    }
+}
+
+/// The config without the station-wide outputs, as for `Args::without_station_outputs`.
+fn program_feeds_config(config: Config) -> Config {
+    Config { smart_feeds: Vec::new(), artist_feeds: None, redirects: BTreeMap::new(), ..config }
+}
+
+/// When a program's feed should next be refreshed: `delay` after the next
+/// episode to end. Airnet only lists episodes up to the one airing or next
+/// to air, so once those have all ended the program is assumed to be weekly.
+pub fn next_refresh(episodes: &[Episode], now: DateTime<Utc>, delay: TimeDelta) -> Option<DateTime<Utc>> {
+    let refresh_after = |end| station_datetime(&end).with_timezone(&Utc) + delay;
+    let upcoming = episodes.iter().map(|episode| refresh_after(episode.end)).filter(|refresh_at| *refresh_at > now).min();
+    if upcoming.is_some() {
+        return upcoming;
+    }
+    let mut end = episodes.iter().map(|episode| episode.end).max()?;
+    // Stepped in station time so the refresh keeps to the show's local air time across daylight saving.
+    while refresh_after(end) <= now {
+        end += TimeDelta::weeks(1);
+    }
+    Some(refresh_after(end))
+}
+
+fn sleep_until(wake_at: DateTime<Utc>, shutdown: &AtomicBool) {
+    while !shutdown.load(Ordering::Relaxed) {
+        let Ok(remaining) = (wake_at - Utc::now()).to_std() else {
+            return;
+        };
+        std::thread::sleep(remaining.min(Duration::from_secs(1)));
+    }
+}
+
+/// An exclusive lock on a file, released when dropped.
+pub struct RunLock {
+    _file: File,
+}
+
+impl RunLock {
+    /// Takes the lock, or returns `None` if another run holds it.
+    pub fn acquire(path: &Path) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let file = File::options().create(true).truncate(false).write(true).open(path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(RunLock { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(err)) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::TimeZone;
+    use tempdir::TempDir;
+
+    fn episode(month: u32, day: u32) -> Episode {
+        test_support::episode(test_support::datetime(month, day, 11))
+    }
+
+    #[test]
+    fn test_next_refresh_after_upcoming_episode() {
+        let episodes = vec![episode(8, 18), episode(8, 25)];
+        // Noon in Melbourne on the 25th, while the second episode airs.
+        let now = Utc.with_ymd_and_hms(2025, 8, 25, 2, 0, 0).unwrap();
+        assert_eq!(
+            next_refresh(&episodes, now, TimeDelta::minutes(15)),
+            Some(Utc.with_ymd_and_hms(2025, 8, 25, 3, 15, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_next_refresh_assumes_weekly() {
+        let episodes = vec![episode(9, 22), episode(9, 29)];
+        // Daylight saving starts on 5 October, so 13:00 is then UTC+11.
+        let now = Utc.with_ymd_and_hms(2025, 10, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            next_refresh(&episodes, now, TimeDelta::minutes(15)),
+            Some(Utc.with_ymd_and_hms(2025, 10, 6, 2, 15, 0).unwrap())
+        );
+        assert_eq!(next_refresh(&[], now, TimeDelta::minutes(15)), None);
+    }
+
+    #[test]
+    fn test_run_lock() {
+        let dir = TempDir::new("daemon").unwrap();
+        let path = dir.path().join(".daemon.lock");
+        let lock = RunLock::acquire(&path).unwrap();
+        assert!(lock.is_some());
+        assert!(RunLock::acquire(&path).unwrap().is_none());
+        drop(lock);
+        assert!(RunLock::acquire(&path).unwrap().is_some());
+    }
+}
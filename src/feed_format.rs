@@ -0,0 +1,192 @@
+use crate::atom::{AtomFeed, AtomLink};
+use crate::json_feed::JsonFeed;
+use crate::rss::Rss;
+use crate::write_rss_v2;
+use chrono::{DateTime, Utc};
+
+/// The formats a program feed can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    pub const ALL: [FeedFormat; 3] = [FeedFormat::Rss, FeedFormat::Atom, FeedFormat::Json];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "rss",
+            FeedFormat::Atom => "atom",
+            FeedFormat::Json => "json",
+        }
+    }
+
+    /// The file a feed in this format is written to, in its feed's directory.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "rss.xml",
+            FeedFormat::Atom => "atom.xml",
+            FeedFormat::Json => "feed.json",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Json => "application/feed+json; charset=utf-8",
+        }
+    }
+
+    /// The media types a client may ask for the format by. The first is the
+    /// format's own.
+    fn media_types(self) -> &'static [&'static str] {
+        match self {
+            FeedFormat::Rss => &["application/rss+xml", "application/xml", "text/xml"],
+            FeedFormat::Atom => &["application/atom+xml"],
+            FeedFormat::Json => &["application/feed+json", "application/json"],
+        }
+    }
+
+    /// The format named by a `?format=` parameter.
+    pub fn from_param(param: &str) -> Option<Self> {
+        let param = param.to_ascii_lowercase();
+        FeedFormat::ALL.into_iter().find(|format| format.name() == param)
+    }
+
+    /// The format that best suits an `Accept` header, preferring RSS when the
+    /// client accepts anything, or `None` when no format is acceptable.
+    pub fn negotiate(accept: Option<&str>) -> Option<Self> {
+        let Some(accept) = accept.filter(|accept| !accept.trim().is_empty()) else {
+            return Some(FeedFormat::Rss);
+        };
+        let ranges: Vec<(String, f32)> = accept.split(',').filter_map(media_range).collect();
+        // The quality of a format is that of the most specific range matching it.
+        let quality = |format: FeedFormat| {
+            ranges
+                .iter()
+                .filter_map(|(range, q)| Some((specificity(range, format)?, *q)))
+                .max_by_key(|(specificity, _)| *specificity)
+                .map(|(_, q)| q)
+                .unwrap_or(0.0)
+        };
+        let mut best: Option<(FeedFormat, f32)> = None;
+        for format in FeedFormat::ALL {
+            let q = quality(format);
+            if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+                best = Some((format, q));
+            }
+        }
+        best.map(|(format, _)| format)
+    }
+
+    /// Renders the feed in this format, linking it to `self_url`, the URL
+    /// it's published at, when known. `now` dates Atom feeds without any dates.
+    pub fn render(
+        self,
+        rss: &Rss,
+        self_url: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut body = Vec::new();
+        match self {
+            FeedFormat::Rss => match self_url.filter(|_| rss.channel().self_link().is_none()) {
+                Some(url) => {
+                    let mut rss = rss.clone();
+                    rss.add_atom_link(crate::rss::AtomLink::self_link(url, self.media_types()[0]));
+                    write_rss_v2(&mut body, &rss, None)?
+                }
+                None => write_rss_v2(&mut body, rss, None)?,
+            },
+            FeedFormat::Atom => {
+                let mut feed = AtomFeed::from_rss(rss, now);
+                if let Some(url) = self_url {
+                    feed.add_link(AtomLink::new(url, Some("self"), Some(self.media_types()[0])));
+                }
+                feed.write(&mut body)?
+            }
+            FeedFormat::Json => {
+                let mut feed = JsonFeed::from_rss(rss);
+                if let Some(url) = self_url {
+                    feed.set_feed_url(url.to_owned());
+                }
+                serde_json::to_writer_pretty(&mut body, &feed)?
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// A media range from an `Accept` header and its quality.
+fn media_range(range: &str) -> Option<(String, f32)> {
+    let mut params = range.split(';');
+    let media_type = params.next()?.trim().to_ascii_lowercase();
+    if media_type.is_empty() {
+        return None;
+    }
+    let q = params
+        .filter_map(|param| param.trim().strip_prefix("q=")?.trim().parse::<f32>().ok())
+        .next()
+        .unwrap_or(1.0);
+    Some((media_type, q))
+}
+
+/// How specifically a media range matches a format, if it does at all.
+fn specificity(range: &str, format: FeedFormat) -> Option<u8> {
+    if range == "*/*" {
+        return Some(0);
+    }
+    if let Some(range_type) = range.strip_suffix("/*") {
+        return format
+            .media_types()
+            .iter()
+            .any(|media_type| media_type.split('/').next() == Some(range_type))
+            .then_some(1);
+    }
+    format.media_types().contains(&range).then_some(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::ChannelBuilder;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(FeedFormat::negotiate(None), Some(FeedFormat::Rss));
+        assert_eq!(FeedFormat::negotiate(Some("*/*")), Some(FeedFormat::Rss));
+        assert_eq!(FeedFormat::negotiate(Some("application/atom+xml")), Some(FeedFormat::Atom));
+        assert_eq!(FeedFormat::negotiate(Some("application/json, */*;q=0.1")), Some(FeedFormat::Json));
+        assert_eq!(
+            FeedFormat::negotiate(Some("application/rss+xml;q=0.5, application/feed+json")),
+            Some(FeedFormat::Json)
+        );
+        assert_eq!(FeedFormat::negotiate(Some("application/rss+xml;q=0, */*")), Some(FeedFormat::Atom));
+        assert_eq!(FeedFormat::negotiate(Some("text/html")), None);
+    }
+
+    #[test]
+    fn test_render_self_link() {
+        let rss = Rss::new(ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "Jazz").build());
+        let url = "https://example.com/feeds/pbsfm/black-wax/";
+        let rendered = |format: FeedFormat| {
+            let file_url = format!("{url}{}", format.file_name());
+            String::from_utf8(format.render(&rss, Some(&file_url), Utc::now()).unwrap()).unwrap()
+        };
+        assert!(rendered(FeedFormat::Rss).contains(
+            r#"<atom:link href="https://example.com/feeds/pbsfm/black-wax/rss.xml" rel="self" type="application/rss+xml"/>"#
+        ));
+        assert!(rendered(FeedFormat::Atom).contains(
+            r#"<link href="https://example.com/feeds/pbsfm/black-wax/atom.xml" rel="self" type="application/atom+xml"/>"#
+        ));
+        assert!(rendered(FeedFormat::Json).contains(r#""feed_url": "https://example.com/feeds/pbsfm/black-wax/feed.json""#));
+    }
+
+    #[test]
+    fn test_from_param() {
+        assert_eq!(FeedFormat::from_param("JSON"), Some(FeedFormat::Json));
+        assert_eq!(FeedFormat::from_param("html"), None);
+    }
+}
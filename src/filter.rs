@@ -0,0 +1,570 @@
+use crate::airnet::types::{Episode, PlaylistEntry};
+use crate::stats::{ContentPercentages, DescriptorCounts};
+use chrono::{Datelike, Weekday};
+use regex::{Regex, RegexBuilder};
+use std::fmt;
+use std::str::FromStr;
+
+/// A boolean expression over an episode and its playlist, e.g.
+/// `title ~ /interview/i and not weekday = sun`.
+///
+/// Conditions are combined with `and`, `or`, `not` and parentheses:
+/// - `title`, `description`, `artist`, `track`, `release` match a `/regex/flags`
+///   with `~`, or a quoted string (case-insensitively) with `=` / `!=`.
+///   Playlist fields match when any track in the episode matches.
+/// - `weekday` compares the day the episode started with `=` / `!=`, e.g. `weekday = sat`.
+/// - `duration` is in minutes; `australian`, `local`, `female`, `indigenous`,
+///   `new` and `gender_non_conforming` are the percentage of tracks with that
+///   content descriptor. All support `=`, `!=`, `<`, `<=`, `>` and `>=`.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Text { field: TextField, matcher: TextMatcher },
+    Weekday { weekday: Weekday, negate: bool },
+    Number { field: NumberField, comparison: Comparison, value: f64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextField {
+    Title,
+    Description,
+    Artist,
+    Track,
+    Release,
+}
+
+#[derive(Debug, Clone)]
+pub enum TextMatcher {
+    Regex(Regex),
+    Equals { value: String, negate: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberField {
+    Duration,
+    Australian,
+    Local,
+    Female,
+    Indigenous,
+    New,
+    GenderNonConforming,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FilterError(String);
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid filter: {}", self.0)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::parse(s)
+    }
+}
+
+impl Filter {
+    pub fn parse(expression: &str) -> Result<Filter, FilterError> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let filter = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(FilterError(format!("unexpected {token}"))),
+        }
+    }
+
+    /// Whether evaluating the filter needs the episode's playlist to be fetched.
+    pub fn needs_playlist(&self) -> bool {
+        match self {
+            Filter::And(a, b) | Filter::Or(a, b) => a.needs_playlist() || b.needs_playlist(),
+            Filter::Not(filter) => filter.needs_playlist(),
+            Filter::Text { field, .. } => {
+                matches!(field, TextField::Artist | TextField::Track | TextField::Release)
+            }
+            Filter::Weekday { .. } => false,
+            Filter::Number { field, .. } => *field != NumberField::Duration,
+        }
+    }
+
+    /// Evaluates the filter; a missing playlist is treated as an empty one.
+    pub fn matches(&self, episode: &Episode, playlist: Option<&[PlaylistEntry]>) -> bool {
+        let playlist = playlist.unwrap_or(&[]);
+        match self {
+            Filter::And(a, b) => a.matches(episode, Some(playlist)) && b.matches(episode, Some(playlist)),
+            Filter::Or(a, b) => a.matches(episode, Some(playlist)) || b.matches(episode, Some(playlist)),
+            Filter::Not(filter) => !filter.matches(episode, Some(playlist)),
+            Filter::Text { field, matcher } => match field {
+                TextField::Title => matcher.matches(episode.title.as_deref()),
+                TextField::Description => matcher.matches(episode.description.as_deref()),
+                TextField::Artist => any_track(playlist, matcher, |entry| entry.artist.as_deref()),
+                TextField::Track => any_track(playlist, matcher, |entry| {
+                    entry.track.as_deref().or(entry.title.as_deref())
+                }),
+                TextField::Release => any_track(playlist, matcher, |entry| entry.release.as_deref()),
+            },
+            Filter::Weekday { weekday, negate } => (episode.start.weekday() == *weekday) != *negate,
+            Filter::Number { field, comparison, value } => {
+                let actual = field.value(episode, playlist);
+                actual.is_some_and(|actual| comparison.compare(actual, *value))
+            }
+        }
+    }
+}
+
+fn any_track(
+    playlist: &[PlaylistEntry],
+    matcher: &TextMatcher,
+    field: impl Fn(&PlaylistEntry) -> Option<&str>,
+) -> bool {
+    let mut tracks = playlist.iter().filter(|entry| entry.is_track());
+    match matcher {
+        // `artist != "x"` means no track is by x, rather than some track is not by x.
+        TextMatcher::Equals { negate: true, .. } => tracks.all(|entry| matcher.matches(field(entry))),
+        _ => tracks.any(|entry| matcher.matches(field(entry))),
+    }
+}
+
+impl TextMatcher {
+    fn matches(&self, text: Option<&str>) -> bool {
+        match self {
+            TextMatcher::Regex(regex) => text.is_some_and(|text| regex.is_match(text)),
+            TextMatcher::Equals { value, negate } => {
+                text.is_some_and(|text| text.to_lowercase() == value.to_lowercase()) != *negate
+            }
+        }
+    }
+}
+
+impl NumberField {
+    /// The episode's duration in minutes, or the percentage of its tracks
+    /// with the descriptor; `None` when the playlist has no tracks.
+    fn value(&self, episode: &Episode, playlist: &[PlaylistEntry]) -> Option<f64> {
+        let percentage = |descriptor: fn(&ContentPercentages) -> f64| {
+            DescriptorCounts::from_playlist(playlist).percentages().map(|percentages| descriptor(&percentages))
+        };
+        match self {
+            NumberField::Duration => Some(episode.duration as f64 / 60.0),
+            NumberField::Australian => percentage(|percentages| percentages.australian),
+            NumberField::Local => percentage(|percentages| percentages.local),
+            NumberField::Female => percentage(|percentages| percentages.female),
+            NumberField::Indigenous => percentage(|percentages| percentages.indigenous),
+            NumberField::New => percentage(|percentages| percentages.new),
+            NumberField::GenderNonConforming => percentage(|percentages| percentages.gender_non_conforming),
+        }
+    }
+}
+
+impl Comparison {
+    fn compare(&self, actual: f64, expected: f64) -> bool {
+        match self {
+            Comparison::Eq => actual == expected,
+            Comparison::Ne => actual != expected,
+            Comparison::Lt => actual < expected,
+            Comparison::Le => actual <= expected,
+            Comparison::Gt => actual > expected,
+            Comparison::Ge => actual >= expected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Regex { pattern: String, flags: String },
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "'{ident}'"),
+            Token::Number(number) => write!(f, "{number}"),
+            Token::Str(s) => write!(f, "\"{s}\""),
+            Token::Regex { pattern, flags } => write!(f, "/{pattern}/{flags}"),
+            Token::Op(op) => write!(f, "'{op}'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+        }
+    }
+}
+
+const OPERATORS: [&str; 7] = ["<=", ">=", "!=", "<", ">", "=", "~"];
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' {
+            chars.next();
+            tokens.push(Token::LParen);
+        } else if c == ')' {
+            chars.next();
+            tokens.push(Token::RParen);
+        } else if c == '/' {
+            chars.next();
+            let mut pattern = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, '/')) => pattern.push('/'),
+                        Some((_, escaped)) => {
+                            pattern.push('\\');
+                            pattern.push(escaped);
+                        }
+                        None => return Err(FilterError("unterminated regex".to_owned())),
+                    },
+                    Some((_, '/')) => break,
+                    Some((_, c)) => pattern.push(c),
+                    None => return Err(FilterError("unterminated regex".to_owned())),
+                }
+            }
+            let mut flags = String::new();
+            while let Some(&(_, flag)) = chars.peek() {
+                if !flag.is_ascii_alphabetic() {
+                    break;
+                }
+                flags.push(flag);
+                chars.next();
+            }
+            tokens.push(Token::Regex { pattern, flags });
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => value.push(escaped),
+                        None => return Err(FilterError("unterminated string".to_owned())),
+                    },
+                    Some((_, quote)) if quote == c => break,
+                    Some((_, c)) => value.push(c),
+                    None => return Err(FilterError("unterminated string".to_owned())),
+                }
+            }
+            tokens.push(Token::Str(value));
+        } else if let Some(op) = OPERATORS.iter().find(|op| expression[start..].starts_with(**op)) {
+            for _ in 0..op.len() {
+                chars.next();
+            }
+            tokens.push(Token::Op(op));
+        } else if c.is_ascii_digit() {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let number = expression[start..end]
+                .parse()
+                .map_err(|_| FilterError(format!("invalid number '{}'", &expression[start..end])))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Ident(expression[start..end].to_lowercase()));
+        } else {
+            return Err(FilterError(format!("unexpected character '{c}'")));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, FilterError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| FilterError("unexpected end of expression".to_owned()))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword)
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, FilterError> {
+        let mut filter = self.parse_and()?;
+        while self.next_is_keyword("or") {
+            self.position += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, FilterError> {
+        let mut filter = self.parse_unary()?;
+        while self.next_is_keyword("and") {
+            self.position += 1;
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, FilterError> {
+        if self.next_is_keyword("not") {
+            self.position += 1;
+            return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.position += 1;
+            let filter = self.parse_or()?;
+            return match self.next()? {
+                Token::RParen => Ok(filter),
+                token => Err(FilterError(format!("expected ')' but found {token}"))),
+            };
+        }
+        self.parse_condition()
+    }
+
+    fn parse_condition(&mut self) -> Result<Filter, FilterError> {
+        let field = match self.next()? {
+            Token::Ident(field) => field,
+            token => return Err(FilterError(format!("expected a field but found {token}"))),
+        };
+        let op = match self.next()? {
+            Token::Op(op) => op,
+            token => return Err(FilterError(format!("expected an operator after '{field}' but found {token}"))),
+        };
+        let value = self.next()?;
+
+        if let Some(field) = text_field(&field) {
+            let matcher = match (op, value) {
+                ("~", Token::Regex { pattern, flags }) => TextMatcher::Regex(build_regex(&pattern, &flags)?),
+                ("=" | "!=", Token::Str(value) | Token::Ident(value)) => {
+                    TextMatcher::Equals { value, negate: op == "!=" }
+                }
+                (op, value) => {
+                    return Err(FilterError(format!("cannot compare text field with '{op}' {value}")));
+                }
+            };
+            return Ok(Filter::Text { field, matcher });
+        }
+
+        if field == "weekday" {
+            let weekday = match &value {
+                Token::Ident(day) | Token::Str(day) => day
+                    .parse::<Weekday>()
+                    .map_err(|_| FilterError(format!("unknown weekday '{day}'")))?,
+                value => return Err(FilterError(format!("expected a weekday but found {value}"))),
+            };
+            return match op {
+                "=" | "!=" => Ok(Filter::Weekday { weekday, negate: op == "!=" }),
+                op => Err(FilterError(format!("cannot compare weekday with '{op}'"))),
+            };
+        }
+
+        if let Some(field) = number_field(&field) {
+            let value = match value {
+                Token::Number(value) => value,
+                value => return Err(FilterError(format!("expected a number but found {value}"))),
+            };
+            let comparison = match op {
+                "=" => Comparison::Eq,
+                "!=" => Comparison::Ne,
+                "<" => Comparison::Lt,
+                "<=" => Comparison::Le,
+                ">" => Comparison::Gt,
+                ">=" => Comparison::Ge,
+                op => return Err(FilterError(format!("cannot compare number with '{op}'"))),
+            };
+            return Ok(Filter::Number { field, comparison, value });
+        }
+
+        Err(FilterError(format!("unknown field '{field}'")))
+    }
+}
+
+fn text_field(field: &str) -> Option<TextField> {
+    match field {
+        "title" => Some(TextField::Title),
+        "description" => Some(TextField::Description),
+        "artist" => Some(TextField::Artist),
+        "track" => Some(TextField::Track),
+        "release" => Some(TextField::Release),
+        _ => None,
+    }
+}
+
+fn number_field(field: &str) -> Option<NumberField> {
+    match field {
+        "duration" => Some(NumberField::Duration),
+        "australian" => Some(NumberField::Australian),
+        "local" => Some(NumberField::Local),
+        "female" => Some(NumberField::Female),
+        "indigenous" => Some(NumberField::Indigenous),
+        "new" => Some(NumberField::New),
+        "gender_non_conforming" => Some(NumberField::GenderNonConforming),
+        _ => None,
+    }
+}
+
+fn build_regex(pattern: &str, flags: &str) -> Result<Regex, FilterError> {
+    let mut builder = RegexBuilder::new(pattern);
+    for flag in flags.chars() {
+        match flag {
+            'i' => builder.case_insensitive(true),
+            'm' => builder.multi_line(true),
+            's' => builder.dot_matches_new_line(true),
+            'x' => builder.ignore_whitespace(true),
+            flag => return Err(FilterError(format!("unknown regex flag '{flag}'"))),
+        };
+    }
+    builder
+        .build()
+        .map_err(|e| FilterError(format!("invalid regex /{pattern}/: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use crate::airnet::types::ContentDescriptors;
+
+    fn episode(title: Option<&str>) -> Episode {
+        Episode {
+            title: title.map(str::to_owned),
+            description: Some("Live in the studio".to_owned()),
+            ..test_support::episode(test_support::datetime(8, 23, 11))
+        }
+    }
+
+    fn track(artist: &str, australian: bool) -> PlaylistEntry {
+        PlaylistEntry {
+            entry_type: "track".to_owned(),
+            id: 1,
+            artist: Some(artist.to_owned()),
+            title: Some("title".to_owned()),
+            track: Some("title".to_owned()),
+            release: Some("release".to_owned()),
+            time: None,
+            notes: None,
+            content_descriptors: Some(ContentDescriptors {
+                is_australian: Some(australian),
+                is_local: None,
+                is_female: None,
+                is_indigenous: None,
+                is_new: None,
+                is_gender_non_conforming: None,
+            }),
+            approximate_time: None,
+        }
+    }
+
+    fn matches(expression: &str, episode: &Episode, playlist: &[PlaylistEntry]) -> bool {
+        Filter::parse(expression).unwrap().matches(episode, Some(playlist))
+    }
+
+    #[test]
+    fn test_title_regex() {
+        let interview = episode(Some("An INTERVIEW with Vince Jones"));
+        assert!(matches("title ~ /interview/i", &interview, &[]));
+        assert!(!matches("title ~ /interview/", &interview, &[]));
+        assert!(!matches("title ~ /interview/i", &episode(None), &[]));
+    }
+
+    #[test]
+    fn test_boolean_operators() {
+        let ep = episode(Some("Interview"));
+        assert!(matches("title ~ /interview/i and description ~ /studio/", &ep, &[]));
+        assert!(matches("title ~ /nope/ or description ~ /studio/", &ep, &[]));
+        assert!(!matches("not (title ~ /nope/ or description ~ /studio/)", &ep, &[]));
+        assert!(matches("not title ~ /nope/ and weekday = sat", &ep, &[]));
+    }
+
+    #[test]
+    fn test_weekday_and_duration() {
+        let ep = episode(None);
+        assert!(matches("weekday = saturday", &ep, &[]));
+        assert!(matches("weekday != sun", &ep, &[]));
+        assert!(matches("duration >= 120", &ep, &[]));
+        assert!(!matches("duration < 60", &ep, &[]));
+    }
+
+    #[test]
+    fn test_playlist_fields() {
+        let ep = episode(None);
+        let playlist = [track("Paul Grabowsky", true), track("Robert Glasper", false)];
+        assert!(matches("artist = 'paul grabowsky'", &ep, &playlist));
+        assert!(matches("artist ~ /glasper/i", &ep, &playlist));
+        assert!(!matches("artist != \"Robert Glasper\"", &ep, &playlist));
+        assert!(matches("australian >= 50", &ep, &playlist));
+        assert!(!matches("australian > 50", &ep, &playlist));
+        assert!(!matches("australian >= 0", &ep, &[]));
+    }
+
+    #[test]
+    fn test_needs_playlist() {
+        assert!(!Filter::parse("title ~ /a/ or duration > 1").unwrap().needs_playlist());
+        assert!(Filter::parse("title ~ /a/ or artist ~ /b/").unwrap().needs_playlist());
+        assert!(Filter::parse("not female > 50").unwrap().needs_playlist());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Filter::parse("colour = red").unwrap_err(),
+            FilterError("unknown field 'colour'".to_owned())
+        );
+        assert_eq!(
+            Filter::parse("title ~ /a/ and").unwrap_err(),
+            FilterError("unexpected end of expression".to_owned())
+        );
+        assert_eq!(
+            Filter::parse("title ~ /a/q").unwrap_err(),
+            FilterError("unknown regex flag 'q'".to_owned())
+        );
+        assert_eq!(
+            Filter::parse("(title ~ /a/").unwrap_err(),
+            FilterError("unexpected end of expression".to_owned())
+        );
+        assert_eq!(
+            Filter::parse("duration > 5 5").unwrap_err(),
+            FilterError("unexpected 5".to_owned())
+        );
+    }
+}
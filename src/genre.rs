@@ -0,0 +1,205 @@
+use crate::aggregate::{aggregate_feed, program_episodes};
+use crate::airnet::types::{Episode, ProgramDetails};
+use crate::rss::{CategoryBuilder, ChannelBuilder, ItunesCategory, Rss};
+use serde::Deserialize;
+
+/// Output directory of the genre feeds, e.g. `pbsfm/genres/jazz/rss.xml`.
+pub const GENRE_FEEDS_DIR: &str = "genres";
+
+/// The `domain` of the genre categories when the config doesn't name one,
+/// identifying the taxonomy as this project's.
+pub const DEFAULT_GENRE_DOMAIN: &str = "https://github.com/aringeri/pbsfm-rss-feed#genres";
+
+/// Apple Podcasts' categories and their subcategories.
+const APPLE_CATEGORIES: [(&str, &[&str]); 19] = [
+    ("Arts", &["Books", "Design", "Fashion & Beauty", "Food", "Performing Arts", "Visual Arts"]),
+    ("Business", &["Careers", "Entrepreneurship", "Investing", "Management", "Marketing", "Non-Profit"]),
+    ("Comedy", &["Comedy Interviews", "Improv", "Stand-Up"]),
+    ("Education", &["Courses", "How To", "Language Learning", "Self-Improvement"]),
+    ("Fiction", &["Comedy Fiction", "Drama", "Science Fiction"]),
+    ("Government", &[]),
+    ("History", &[]),
+    ("Health & Fitness", &["Alternative Health", "Fitness", "Medicine", "Mental Health", "Nutrition", "Sexuality"]),
+    ("Kids & Family", &["Education for Kids", "Parenting", "Pets & Animals", "Stories for Kids"]),
+    (
+        "Leisure",
+        &["Animation & Manga", "Automotive", "Aviation", "Crafts", "Games", "Hobbies", "Home & Garden", "Video Games"],
+    ),
+    ("Music", &["Music Commentary", "Music History", "Music Interviews"]),
+    (
+        "News",
+        &["Business News", "Daily News", "Entertainment News", "News Commentary", "Politics", "Sports News", "Tech News"],
+    ),
+    (
+        "Religion & Spirituality",
+        &["Buddhism", "Christianity", "Hinduism", "Islam", "Judaism", "Religion", "Spirituality"],
+    ),
+    (
+        "Science",
+        &[
+            "Astronomy", "Chemistry", "Earth Sciences", "Life Sciences", "Mathematics", "Natural Sciences", "Nature",
+            "Physics", "Social Sciences",
+        ],
+    ),
+    ("Society & Culture", &["Documentary", "Personal Journals", "Philosophy", "Places & Travel", "Relationships"]),
+    (
+        "Sports",
+        &[
+            "Baseball", "Basketball", "Cricket", "Fantasy Sports", "Football", "Golf", "Hockey", "Rugby", "Running",
+            "Soccer", "Swimming", "Tennis", "Volleyball", "Wilderness", "Wrestling",
+        ],
+    ),
+    ("Technology", &[]),
+    ("True Crime", &[]),
+    ("TV & Film", &["After Shows", "Film History", "Film Interviews", "Film Reviews", "TV Reviews"]),
+];
+
+/// A genre of the station's own taxonomy, which programs are put in by slug
+/// or by their free-text grid description, and which maps to an Apple
+/// Podcasts category.
+///
+/// ```toml
+/// [[genres]]
+/// slug = "jazz"
+/// name = "Jazz"
+/// apple_category = "Music"
+/// programs = ["black-wax"]
+/// grid_descriptions = ["jazz", "swing"]
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct Genre {
+    pub slug: String,
+    pub name: String,
+    pub apple_category: String,
+    pub apple_subcategory: Option<String>,
+    /// Program slugs in the genre, whatever their grid description.
+    #[serde(default)]
+    pub programs: Vec<String>,
+    /// Programs whose grid description contains any of these, ignoring case, are in the genre.
+    #[serde(default)]
+    pub grid_descriptions: Vec<String>,
+}
+
+impl Genre {
+    /// Checks the Apple category and subcategory are ones Apple Podcasts lists.
+    pub fn validate(&self) -> Result<(), String> {
+        let (_, subcategories) = APPLE_CATEGORIES
+            .iter()
+            .find(|(category, _)| *category == self.apple_category)
+            .ok_or_else(|| format!("genre {}: {:?} is not an Apple Podcasts category", self.slug, self.apple_category))?;
+        match &self.apple_subcategory {
+            Some(subcategory) if !subcategories.contains(&subcategory.as_str()) => Err(format!(
+                "genre {}: {subcategory:?} is not an Apple Podcasts subcategory of {}",
+                self.slug, self.apple_category
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn matches_grid_description(&self, grid_description: Option<&str>) -> bool {
+        let Some(grid_description) = grid_description.map(str::to_lowercase) else {
+            return false;
+        };
+        self.grid_descriptions.iter().any(|text| grid_description.contains(&text.to_lowercase()))
+    }
+
+    /// Adds the genre to a program's feed, as its `itunes:category` and as a
+    /// category qualified by the taxonomy's `domain`.
+    pub fn categorize(&self, domain: &str, rss: &mut Rss) {
+        let channel = rss.channel_mut();
+        channel.set_itunes_category(self.itunes_category());
+        channel.add_category(CategoryBuilder::new(&self.name).domain(domain.to_owned()).build());
+    }
+
+    pub fn itunes_category(&self) -> ItunesCategory {
+        let category = ItunesCategory::new(&self.apple_category);
+        match &self.apple_subcategory {
+            Some(subcategory) => category.with_subcategory(subcategory),
+            None => category,
+        }
+    }
+}
+
+/// The genre of the program: the first listing it by slug, otherwise the
+/// first matching its grid description.
+pub fn genre_of<'a>(genres: &'a [Genre], program: &ProgramDetails) -> Option<&'a Genre> {
+    genres.iter().find(|genre| genre.programs.contains(&program.slug)).or_else(|| {
+        genres.iter().find(|genre| genre.matches_grid_description(program.grid_description.as_deref()))
+    })
+}
+
+/// Builds the feed of the episodes of every program in the genre, categorized
+/// under the taxonomy's `domain`.
+pub fn genre_feed(genre: &Genre, genres: &[Genre], domain: &str, programs: &[(ProgramDetails, Vec<Episode>)]) -> Rss {
+    let episodes = program_episodes(programs)
+        .into_iter()
+        .filter(|(program, _)| genre_of(genres, program).is_some_and(|of| of.slug == genre.slug))
+        .collect();
+    let mut rss = aggregate_feed(
+        ChannelBuilder::new(
+            format!("PBS 106.7FM - {}", genre.name),
+            "https://www.pbsfm.org.au",
+            format!("Episodes of every {} program on PBS 106.7FM", genre.name),
+        ),
+        episodes,
+    );
+    let channel = rss.channel_mut();
+    channel.set_itunes_category(genre.itunes_category());
+    channel.add_category(CategoryBuilder::new(&genre.name).domain(domain.to_owned()).build());
+    rss
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn genre(slug: &str, programs: &[&str], grid_descriptions: &[&str]) -> Genre {
+        Genre {
+            slug: slug.to_owned(),
+            name: slug.to_uppercase(),
+            apple_category: "Music".to_owned(),
+            apple_subcategory: None,
+            programs: programs.iter().map(|program| program.to_string()).collect(),
+            grid_descriptions: grid_descriptions.iter().map(|text| text.to_string()).collect(),
+        }
+    }
+
+    fn program(slug: &str, grid_description: &str) -> ProgramDetails {
+        ProgramDetails { grid_description: Some(grid_description.to_owned()), ..test_support::program(slug) }
+    }
+
+    #[test]
+    fn test_genre_of() {
+        let genres = [genre("jazz", &[], &["jazz"]), genre("soul", &["black-wax"], &["soul", "funk"])];
+        let slug = |program: &ProgramDetails| genre_of(&genres, program).map(|genre| genre.slug.as_str());
+        assert_eq!(slug(&program("jazz-spot", "Groovin' JAZZ")), Some("jazz"));
+        assert_eq!(slug(&program("black-wax", "Groovin' jazz")), Some("soul"));
+        assert_eq!(slug(&program("soul-time", "Soul and R&B")), Some("soul"));
+        assert_eq!(slug(&program("metal-show", "Heavy metal")), None);
+    }
+
+    #[test]
+    fn test_categorize() {
+        let genres = [Genre { apple_subcategory: Some("Music Commentary".to_owned()), ..genre("jazz", &[], &["jazz"]) }];
+        let mut rss = Rss::new(ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "").build());
+        genre_of(&genres, &program("black-wax", "Groovin' jazz")).unwrap().categorize(DEFAULT_GENRE_DOMAIN, &mut rss);
+        let channel = rss.channel();
+        assert_eq!(
+            channel.itunes_category(),
+            Some(&ItunesCategory::new("Music").with_subcategory("Music Commentary"))
+        );
+        assert_eq!(
+            channel.categories(),
+            [CategoryBuilder::new("JAZZ").domain(DEFAULT_GENRE_DOMAIN.to_owned()).build()]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(genre("jazz", &[], &[]).validate().is_ok());
+        assert!(Genre { apple_subcategory: Some("Music History".to_owned()), ..genre("jazz", &[], &[]) }.validate().is_ok());
+        assert!(Genre { apple_category: "Jazz".to_owned(), ..genre("jazz", &[], &[]) }.validate().is_err());
+        assert!(Genre { apple_subcategory: Some("Jazz".to_owned()), ..genre("jazz", &[], &[]) }.validate().is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::airnet::types::Episode;
+use crate::rss::{Item, ItemGuid, ItemGuidBuilder, Rss};
+use rss_gen::{RssData, RssItem};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A guid unique to the episode, even among several aired on the same day:
+/// its Airnet REST URL.
+pub fn episode_guid(episode: &Episode) -> ItemGuid {
+    ItemGuidBuilder::new(&episode.episode_rest_url).is_permalink(false).build()
+}
+
+/// The guids of the items in a previously written feed, by item link.
+///
+/// Feeds written before guids came from Airnet used the episode link as the
+/// guid. Podcast clients treat an item with a new guid as a new episode, so
+/// items already published keep whatever guid they were published with.
+#[derive(Default, Debug)]
+pub struct PublishedGuids {
+    by_link: HashMap<String, ItemGuid>,
+    guids: HashSet<String>,
+    /// Whether there was a previously written feed at all.
+    feed_found: bool,
+}
+
+impl PublishedGuids {
+    /// Reads the guids from the feed at `path`, if one was written there before.
+    pub fn read(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            return Ok(PublishedGuids::default());
+        }
+        let xml = std::fs::read_to_string(path)?;
+        let rss = Rss::from_xml(&xml)
+            .map_err(|err| format!("could not read published guids from {}: {err}", path.display()))?;
+        Ok(PublishedGuids::from_feed(&rss))
+    }
+
+    pub fn from_feed(rss: &Rss) -> Self {
+        let items = rss.channel().items();
+        let by_link = items
+            .iter()
+            .filter_map(|item| Some((item.link()?.to_owned(), item.guid()?.clone())))
+            .collect();
+        let guids = items.iter().filter_map(|item| Some(item.guid()?.value().to_owned())).collect();
+        PublishedGuids { by_link, guids, feed_found: true }
+    }
+
+    /// Whether the item wasn't in the previously written feed. Nothing is new
+    /// when there was no previous feed, as every item would be.
+    pub fn is_new(&self, item: &Item) -> bool {
+        self.is_new_episode(item.link(), item.guid().map(ItemGuid::value))
+    }
+
+    /// As `is_new`, for feeds written with the legacy serialization.
+    pub fn is_new_legacy(&self, item: &RssItem) -> bool {
+        self.is_new_episode(Some(&item.link), Some(&item.guid))
+    }
+
+    fn is_new_episode(&self, link: Option<&str>, guid: Option<&str>) -> bool {
+        self.feed_found
+            && !link.is_some_and(|link| self.by_link.contains_key(link))
+            && !guid.is_some_and(|guid| self.guids.contains(guid))
+    }
+
+    /// Gives the items that were already published their published guid.
+    pub fn apply(&self, rss: &mut Rss) {
+        for item in rss.channel_mut().items_mut() {
+            if let Some(guid) = item.link().and_then(|link| self.by_link.get(link)) {
+                item.set_guid(guid.clone());
+            }
+        }
+    }
+
+    /// As `apply`, for feeds written with the legacy serialization.
+    pub fn apply_legacy(&self, rss_data: &mut RssData) {
+        for item in &mut rss_data.items {
+            if let Some(guid) = self.by_link.get(&item.link) {
+                item.guid = guid.value().to_owned();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::{ChannelBuilder, ItemBuilder};
+
+    fn feed(guid: ItemGuid) -> Rss {
+        Rss::new(
+            ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "Jazz")
+                .item(vec![
+                    ItemBuilder::with_title("Episode")
+                        .link("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00".to_owned())
+                        .guid(guid)
+                        .build(),
+                ])
+                .build(),
+        )
+    }
+
+    #[test]
+    fn test_keeps_published_guid() {
+        let published = ItemGuidBuilder::new("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00").build();
+        let mut rss = feed(ItemGuidBuilder::new("https://airnet/episodes/2025-06-16").is_permalink(false).build());
+
+        PublishedGuids::from_feed(&feed(published.clone())).apply(&mut rss);
+        assert_eq!(rss.channel().items()[0].guid(), Some(&published));
+    }
+
+    #[test]
+    fn test_is_new() {
+        let published = feed(ItemGuidBuilder::new("https://airnet/episodes/2025-06-16").is_permalink(false).build());
+        let item = |link: &str, guid: &str| {
+            ItemBuilder::with_title("Episode")
+                .link(link.to_owned())
+                .guid(ItemGuidBuilder::new(guid).is_permalink(false).build())
+                .build()
+        };
+        let published_guids = PublishedGuids::from_feed(&published);
+        assert!(!published_guids.is_new(&published.channel().items()[0]));
+        assert!(published_guids.is_new(&item("https://www.pbsfm.org.au/program/black-wax/2025-06-23/11-00-00", "https://airnet/episodes/2025-06-23")));
+        assert!(!PublishedGuids::default().is_new(&item("https://link", "https://guid")));
+    }
+
+    #[test]
+    fn test_new_items_keep_their_guid() {
+        let guid = ItemGuidBuilder::new("https://airnet/episodes/2025-06-16").is_permalink(false).build();
+        let mut rss = feed(guid.clone());
+
+        PublishedGuids::default().apply(&mut rss);
+        assert_eq!(rss.channel().items()[0].guid(), Some(&guid));
+    }
+}
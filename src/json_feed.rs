@@ -0,0 +1,147 @@
+use crate::atom::item_date;
+use crate::rss::{Item, Rss};
+use chrono::SecondsFormat;
+use serde::Serialize;
+
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+/// A JSON Feed 1.1 with the same items as an RSS feed.
+#[serde_with::apply(
+    Option => #[serde(skip_serializing_if = "Option::is_none")],
+)]
+#[derive(Serialize, Debug, PartialEq)]
+pub struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: Option<String>,
+    description: Option<String>,
+    icon: Option<String>,
+    language: Option<String>,
+    items: Vec<JsonFeedItem>,
+}
+
+#[serde_with::apply(
+    Option => #[serde(skip_serializing_if = "Option::is_none")],
+    Vec => #[serde(skip_serializing_if = "Vec::is_empty")],
+)]
+#[derive(Serialize, Debug, PartialEq)]
+pub struct JsonFeedItem {
+    id: String,
+    url: Option<String>,
+    title: Option<String>,
+    content_text: String,
+    date_published: Option<String>,
+    authors: Vec<JsonFeedAuthor>,
+    attachments: Vec<JsonFeedAttachment>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct JsonFeedAuthor {
+    name: String,
+}
+
+#[serde_with::apply(
+    Option => #[serde(skip_serializing_if = "Option::is_none")],
+)]
+#[derive(Serialize, Debug, PartialEq)]
+pub struct JsonFeedAttachment {
+    url: String,
+    mime_type: String,
+    size_in_bytes: Option<u64>,
+}
+
+impl JsonFeed {
+    pub fn from_rss(rss: &Rss) -> Self {
+        let channel = rss.channel();
+        JsonFeed {
+            version: JSON_FEED_VERSION,
+            title: channel.title().to_owned(),
+            home_page_url: channel.link().to_owned(),
+            feed_url: None,
+            description: Some(channel.description().to_owned()).filter(|description| !description.is_empty()),
+            icon: channel
+                .itunes_image()
+                .map(|artwork| artwork.href().to_owned())
+                .or(channel.image().map(|image| image.url().to_owned())),
+            language: channel.language().map(str::to_owned),
+            items: channel.items().iter().map(JsonFeedItem::from_item).collect(),
+        }
+    }
+
+    /// Sets the URL the feed itself is served from.
+    pub fn set_feed_url(&mut self, feed_url: String) {
+        self.feed_url = Some(feed_url);
+    }
+}
+
+impl JsonFeedItem {
+    fn from_item(item: &Item) -> Self {
+        JsonFeedItem {
+            id: item
+                .guid()
+                .map(|guid| guid.value())
+                .or(item.link())
+                .unwrap_or_default()
+                .to_owned(),
+            url: item.link().map(str::to_owned),
+            title: item.title().map(str::to_owned),
+            content_text: item.description().unwrap_or_default().to_owned(),
+            date_published: item_date(item).map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, false)),
+            authors: item
+                .author()
+                .map(|name| JsonFeedAuthor { name: name.to_owned() })
+                .into_iter()
+                .collect(),
+            attachments: item
+                .enclosure()
+                .map(|enclosure| JsonFeedAttachment {
+                    url: enclosure.url().to_owned(),
+                    mime_type: enclosure.media_type().to_owned(),
+                    size_in_bytes: enclosure.length().filter(|length| *length > 0),
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::{ChannelBuilder, Enclosure, ItemBuilder};
+
+    #[test]
+    fn test_from_rss() {
+        let rss = Rss::new(
+            ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "Jazz")
+                .language("en".to_owned())
+                .item(vec![
+                    ItemBuilder::with_title("Episode")
+                        .link("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00".to_owned())
+                        .enclosure(Enclosure::new("https://airnet/audio.m4a", Some(0), "audio/mp4"))
+                        .pub_date("Mon, 16 Jun 2025 11:00:00 +1000".to_owned())
+                        .build(),
+                ])
+                .build(),
+        );
+        assert_eq!(
+            serde_json::to_value(JsonFeed::from_rss(&rss)).unwrap(),
+            serde_json::json!({
+                "version": "https://jsonfeed.org/version/1.1",
+                "title": "Black Wax",
+                "home_page_url": "https://www.pbsfm.org.au/program/black-wax",
+                "description": "Jazz",
+                "language": "en",
+                "items": [{
+                    "id": "https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00",
+                    "url": "https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00",
+                    "title": "Episode",
+                    "content_text": "",
+                    "date_published": "2025-06-16T11:00:00+10:00",
+                    "attachments": [{ "url": "https://airnet/audio.m4a", "mime_type": "audio/mp4" }],
+                }],
+            })
+        );
+    }
+}
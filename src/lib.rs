@@ -1,21 +1,103 @@
-use crate::airnet::types::{Episode, ProgramDetails};
-use clap::Parser;
+use crate::airnet::types::{Episode, ProgramDescription, ProgramDetails};
+use chrono::{DateTime, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Australia::Melbourne;
+use clap::{Parser, Subcommand};
 use quick_xml::writer::Writer;
 use rss_gen::macro_write_element;
 use rss_gen::{RssData, RssItem, RssVersion};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::BufWriter;
-use std::path::PathBuf;
-use quick_xml::events::{BytesDecl, Event};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use quick_xml::events::{BytesDecl, BytesPI, Event};
 use regex::Regex;
-use crate::rss::{CategoryBuilder, ChannelBuilder, Enclosure, ImageBuilder, Item, ItemBuilder, ItemGuidBuilder, Rss};
+use crate::rss::{CategoryBuilder, ChannelBuilder, Enclosure, ImageBuilder, Item, ItemBuilder, ItunesImage, PodcastPerson, Rss};
+use crate::airnet::PlaylistCache;
+use crate::archive::Archive;
+use crate::artist_feed::ArtistFeeds;
+use crate::config::Config;
+use crate::daemon::DaemonArgs;
+use crate::feed_format::FeedFormat;
+use crate::guid::PublishedGuids;
+use crate::manifest::Manifest;
+use crate::opml::Opml;
+use crate::retire::Retirement;
+use crate::commands::{ListProgramsArgs, ShowArgs, StatsArgs, ValidateArgs};
+use crate::search::SearchArgs;
+use crate::serve::ServeArgs;
+use crate::site::{FeedLinks, Site};
+use crate::smart_feed::SmartFeed;
+use crate::stats::{ProgramStats, ReportFormat};
+use crate::validation::ArtworkChecker;
+use crate::webhook::EpisodePublished;
 
+pub mod aggregate;
+pub mod api;
 pub mod airnet;
+pub mod archive;
+pub mod artist_feed;
+pub mod atom;
+pub mod changelog;
+pub mod commands;
+pub mod config;
+pub mod daemon;
+pub mod feed_format;
+pub mod filter;
+pub mod genre;
+pub mod guid;
+pub mod json_feed;
+pub mod live;
+pub mod manifest;
+pub mod opml;
+pub mod presenter;
+pub mod redirect;
+pub mod retire;
 pub mod rss_macros;
 pub mod rss;
+pub mod search;
+pub mod serve;
+pub mod site;
+pub mod smart_feed;
+pub mod stats;
+#[cfg(test)]
+mod test_support;
+pub mod validation;
+pub mod webhook;
+pub mod websub;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub args: Args,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate feeds for the given programs (the default when no subcommand is given)
+    Generate(Args),
+    /// List every program on the station with its slug, archived flag and broadcasters
+    ListPrograms(ListProgramsArgs),
+    /// Show a program's details and most recent episodes
+    Show(ShowArgs),
+    /// Print, or write to a file, the content quota report of the given programs
+    Stats(StatsArgs),
+    /// Check a feed against RSS 2.0 and podcast directory rules
+    Validate(ValidateArgs),
+    /// Serve program feeds over HTTP, generating them from Airnet on request
+    Serve(ServeArgs),
+    /// Keep running, refreshing each program's feed shortly after its episodes end
+    Daemon(DaemonArgs),
+    /// Search archived episodes and tracklists without contacting Airnet
+    Search(SearchArgs),
+    /// Print the OpenAPI document describing the JSON API served by `serve`
+    Openapi,
+}
+
+#[derive(clap::Args, Debug, Clone)]
 pub struct Args {
     #[arg(short, long, default_value = "https://airnet.org.au")]
     pub airnet_url: String,
@@ -28,38 +110,731 @@ pub struct Args {
 
     #[arg(short, long, default_value_t = false)]
     pub use_custom_rss_serialization: bool,
+
+    /// Append Australian/local/female/... content percentages to each item description
+    #[arg(long, default_value_t = false)]
+    pub content_stats_in_description: bool,
+
+    /// Write a content quota report for all programs to `pbsfm/stats.<format>`
+    #[arg(long, value_enum)]
+    pub stats_format: Option<ReportFormat>,
+
+    /// Also write a station-wide feed of every processed program to `pbsfm/all/rss.xml`
+    #[arg(long, default_value_t = false)]
+    pub combined_feed: bool,
+
+    /// Number of days of episodes, counted back from the most recent one, kept in the combined feed
+    #[arg(long, default_value_t = 7)]
+    pub combined_feed_days: u32,
+
+    /// Also write a feed for each presenter, under `pbsfm/presenters/`, of the episodes of every program they present
+    #[arg(long, default_value_t = false, requires = "use_custom_rss_serialization")]
+    pub presenter_feeds: bool,
+
+    /// Also write a feed for each genre in the config, under `pbsfm/genres/`, of the episodes of every program in it
+    #[arg(long, default_value_t = false, requires = "use_custom_rss_serialization")]
+    pub genre_feeds: bool,
+
+    /// Also write a feed of changes to the station's programs, under `pbsfm/changelog/`, found by comparing Airnet's program list with the one saved there by the last run
+    #[arg(long, default_value_t = false)]
+    pub changelog_feed: bool,
+
+    /// TOML file with additional outputs, such as smart feeds
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// SQLite database recording every program, episode and playlist fetched
+    #[arg(long)]
+    pub archive: Option<PathBuf>,
+
+    /// Generate program feeds from every archived episode instead of only Airnet's current window
+    #[arg(long, default_value_t = false, requires = "archive")]
+    pub feed_from_archive: bool,
+
+    /// Fail the run if any written feed has validation errors, after writing every feed
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+
+    /// Station live stream, added to program feeds as a `podcast:liveItem` while the program is on air
+    #[arg(long, requires = "use_custom_rss_serialization")]
+    pub live_stream_url: Option<String>,
+
+    /// Public URL the output directory is served from, e.g. https://aringeri.github.io/pbsfm-rss-feed/feeds/, giving each feed the canonical URL it links to as its self link
+    #[arg(long)]
+    pub public_base_url: Option<String>,
+
+    /// WebSub hub linked from every RSS feed and notified when one changes
+    #[arg(long = "websub-hub", requires_all = ["public_base_url", "use_custom_rss_serialization"])]
+    pub websub_hubs: Vec<String>,
+
+    /// Also write a static site listing every program, with a page of episodes and tracklists for each, to this directory
+    #[arg(long)]
+    pub site_dir: Option<PathBuf>,
+
+    /// Link every feed to an XSL stylesheet, written to `feed.xsl` in the output directory, so browsers show a readable page instead of raw XML
+    #[arg(long, default_value_t = false)]
+    pub feed_stylesheet: bool,
+
+    /// Also write each feed in these formats, as `atom.xml` or `feed.json` next to its `rss.xml`
+    #[arg(long = "extra-format", value_enum, value_delimiter = ',')]
+    pub extra_formats: Vec<FeedFormat>,
+
+    /// When a program is archived or gone from Airnet, add a final episode announcing that it has ended to its feed
+    #[arg(long, default_value_t = false)]
+    pub retirement_item: bool,
+
+    /// Write only the programs' own pages of the site, leaving the index,
+    /// which lists every program, to runs of all of them
+    #[arg(skip)]
+    pub site_program_pages_only: bool,
+}
+
+impl Args {
+    /// The arguments for generating only these programs' own feeds and site
+    /// pages, with the station-wide outputs, which need every program, switched off.
+    pub fn without_station_outputs(&self, programs: Vec<String>) -> Args {
+        Args {
+            programs,
+            stats_format: None,
+            combined_feed: false,
+            presenter_feeds: false,
+            genre_feeds: false,
+            changelog_feed: false,
+            site_program_pages_only: true,
+            ..self.clone()
+        }
+    }
+}
+
+/// Data gathered while generating each program's feed, used by the
+/// station-wide outputs written once all programs have been processed.
+struct Collected {
+    started_at: DateTime<Utc>,
+    program_stats: Vec<ProgramStats>,
+    programs: Vec<(ProgramDetails, Vec<Episode>)>,
+    playlists: PlaylistCache,
+    invalid_feeds: Vec<PathBuf>,
+    artwork: ArtworkChecker,
+    changed_feeds: Vec<PathBuf>,
+    new_episodes: Vec<EpisodePublished>,
+    /// RFC 3339 retirement time of the programs whose feeds were retired, by slug.
+    retired: BTreeMap<String, String>,
+}
+
+impl Collected {
+    fn new(started_at: DateTime<Utc>) -> Self {
+        Collected {
+            started_at,
+            program_stats: Vec::new(),
+            programs: Vec::new(),
+            playlists: PlaylistCache::default(),
+            invalid_feeds: Vec::new(),
+            artwork: ArtworkChecker::default(),
+            changed_feeds: Vec::new(),
+            new_episodes: Vec::new(),
+            retired: BTreeMap::new(),
+        }
+    }
+
+    /// Writes the feed unless the file already has the same contents,
+    /// remembering the feeds that changed.
+    fn write_if_changed(&mut self, path: &Path, feed: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        if std::fs::read(path).is_ok_and(|written| written == feed) {
+            return Ok(());
+        }
+        std::fs::write(path, feed)?;
+        self.changed_feeds.push(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Reads back a feed just written, printing any validation issues and
+    /// remembering it if it has errors.
+    fn validate_feed(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let issues = validation::validate_with_artwork(&validation::read_feed(path)?, &mut self.artwork);
+        for issue in &issues {
+            println!("{}: {issue}", path.display());
+        }
+        if validation::has_errors(&issues) {
+            self.invalid_feeds.push(path.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Fetches the programs, by slug, that weren't already fetched for the per-program feeds.
+    fn fetch_programs(&mut self, airnet_url: &str, slugs: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        for slug in slugs {
+            if !self.programs.iter().any(|(program, _)| &program.slug == slug) {
+                let fetched = generate_rss_feed(airnet_url, slug, |program, episodes| {
+                    Ok((program, live::aired_episodes(episodes, self.started_at)))
+                })?;
+                self.programs.push(fetched);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches the playlist of every episode of the fetched programs accepted by `include_program`.
+    fn fetch_playlists(
+        &mut self,
+        client: &airnet::AirnetClient,
+        include_program: impl Fn(&str) -> bool,
+    ) -> Result<(), reqwest::Error> {
+        for (program, episodes) in &self.programs {
+            if include_program(&program.slug) {
+                for episode in episodes {
+                    self.playlists.fetch(client, PBSFM_STATION, &program.slug, &episode.start)?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
-const PBSFM_STATION: &str = "3pbs";
+pub(crate) const PBSFM_STATION: &str = "3pbs";
+
+pub fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    match cli.command {
+        Some(Command::Generate(args)) => run_app(args),
+        Some(Command::ListPrograms(list_args)) => commands::run_list_programs(list_args),
+        Some(Command::Show(show_args)) => commands::run_show(show_args),
+        Some(Command::Stats(stats_args)) => commands::run_stats(stats_args),
+        Some(Command::Validate(validate_args)) => commands::run_validate(validate_args),
+        Some(Command::Serve(serve_args)) => serve::run_serve(serve_args),
+        Some(Command::Daemon(daemon_args)) => daemon::run_daemon(daemon_args),
+        Some(Command::Search(search_args)) => search::run_search(search_args),
+        Some(Command::Openapi) => {
+            println!("{}", serde_json::to_string_pretty(&api::openapi_document())?);
+            Ok(())
+        }
+        None => run_app(cli.args),
+    }
+}
 
 pub fn run_app(args: Args) -> Result<(), Box<dyn std::error::Error>> {
-    for program in args.programs {
+    run_app_at(args, Utc::now())
+}
 
-        let station_dir = args.output_dir.join("pbsfm/").join(&program);
+/// Generates the feeds as if it were `now`, which decides the episodes that
+/// have aired and the one on air.
+pub fn run_app_at(args: Args, now: DateTime<Utc>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    generate_feeds(args, &config, now)
+}
+
+/// As `run_app_at`, with the `--config` file already loaded.
+pub(crate) fn generate_feeds(args: Args, config: &Config, now: DateTime<Utc>) -> Result<(), Box<dyn std::error::Error>> {
+    if !config.genres.is_empty() && !args.use_custom_rss_serialization {
+        return Err("genres need --use-custom-rss-serialization".into());
+    }
+    if let Some(old_slug) = config.redirects.keys().find(|old_slug| args.programs.contains(old_slug)) {
+        return Err(format!("{old_slug} is redirected, so its feed can't also be generated").into());
+    }
+    let archive = args.archive.as_deref().map(Archive::open).transpose()?;
+    let client = airnet::AirnetClient::new(args.airnet_url.clone());
+    let mut collected = Collected::new(now);
+
+    let all_programs = client.all_programs(PBSFM_STATION)?;
+    if let Some(archive) = &archive {
+        archive.record_program_descriptions(&all_programs, collected.started_at)?;
+    }
+    let archived: Vec<&String> =
+        all_programs.iter().filter(|program| program.archived).filter_map(|program| program.slug.as_ref()).collect();
+
+    for program in &args.programs {
+        let station_dir = args.output_dir.join("pbsfm/").join(program);
         std::fs::create_dir_all(&station_dir)?;
+        let out_path = station_dir.join("rss.xml");
 
-        let out_file = File::create(station_dir.join("rss.xml"))?;
+        if archived.contains(&program) {
+            retire_program(&args, program, &out_path, Retirement::Archived, &mut collected)?;
+            continue;
+        }
+        match write_program_feed(&args, config, &client, archive.as_ref(), program, &out_path, &mut collected) {
+            Err(err) if retire::is_not_found(err.as_ref()) => {
+                retire_program(&args, program, &out_path, Retirement::NotFound, &mut collected)?
+            }
+            result => result?,
+        }
+    }
 
-        if args.use_custom_rss_serialization {
-            let rss_feed = generate_rss_feed(&args.airnet_url, &program, convert_to_rss_v2)?;
-            let mut writer = Writer::new_with_indent(BufWriter::new(out_file), b' ', 2);
+    if args.feed_stylesheet {
+        site::write_feed_stylesheet(&args.output_dir)?;
+    }
 
-            writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
-            writer.write_serializable("rss", &rss_feed)?;
-        } else {
-            let rss_feed = generate_rss_feed(&args.airnet_url, &program, convert_to_rss)?;
-            let writer = Writer::new_with_indent(BufWriter::new(out_file), b' ', 2);
-            let r: Result<_, std::io::Error> =
-                macro_generate_rss_custom!(writer, rss_feed).map(|_writer| ());
-            r?;
+    if let Some(format) = args.stats_format {
+        let station_dir = args.output_dir.join("pbsfm/");
+        std::fs::create_dir_all(&station_dir)?;
+        let report_file = File::create(station_dir.join(format!("stats.{}", format.extension())))?;
+        stats::write_report(BufWriter::new(report_file), &collected.program_stats, format)?;
+    }
+
+    if args.combined_feed {
+        let combined_dir = args.output_dir.join("pbsfm/").join(aggregate::COMBINED_FEED_SLUG);
+        std::fs::create_dir_all(&combined_dir)?;
+        let rss_feed = aggregate::combined_feed(
+            &collected.programs,
+            chrono::Duration::days(args.combined_feed_days.into()),
+            collected.started_at,
+        );
+        write_feed(&args, &combined_dir.join("rss.xml"), rss_feed, &mut collected)?;
+    }
+
+    if args.presenter_feeds {
+        for (slug, name) in presenter::presenters(&collected.programs) {
+            let rss_feed = presenter::presenter_feed(&name, &slug, &collected.programs);
+            let presenter_dir = args.output_dir.join("pbsfm/").join(presenter::PRESENTER_FEEDS_DIR).join(&slug);
+            std::fs::create_dir_all(&presenter_dir)?;
+            write_feed(&args, &presenter_dir.join("rss.xml"), rss_feed, &mut collected)?;
+        }
+    }
+
+    if args.genre_feeds {
+        for genre in &config.genres {
+            let rss_feed = genre::genre_feed(genre, &config.genres, config.genre_domain(), &collected.programs);
+            let genre_dir = args.output_dir.join("pbsfm/").join(genre::GENRE_FEEDS_DIR).join(&genre.slug);
+            std::fs::create_dir_all(&genre_dir)?;
+            write_feed(&args, &genre_dir.join("rss.xml"), rss_feed, &mut collected)?;
+        }
+    }
+
+    if args.changelog_feed {
+        write_changelog_feed(&args, &all_programs, &mut collected)?;
+    }
+
+    for smart_feed in &config.smart_feeds {
+        write_smart_feed(&args, &client, smart_feed, &mut collected)?;
+    }
+
+    if let Some(artist_feeds) = &config.artist_feeds {
+        write_artist_feeds(&args, &client, artist_feeds, &mut collected)?;
+    }
+
+    for (old_slug, new_slug) in &config.redirects {
+        write_redirect(&args, old_slug, new_slug, &mut collected)?;
+    }
+
+    if let Some(site_dir) = &args.site_dir {
+        write_site(&args, &client, site_dir, &mut collected)?;
+    }
+
+    if let Some(archive) = &archive {
+        for ((program_slug, episode_start), playlist) in collected.playlists.fetched() {
+            archive.record_playlist(program_slug, episode_start, playlist, collected.started_at)?;
+        }
+    }
+
+    let manifest = Manifest::read(&args.output_dir, |path| feed_href(&args, path))?;
+    std::fs::write(args.output_dir.join(manifest::MANIFEST_FILE), serde_json::to_vec_pretty(&manifest)?)?;
+    let mut opml = Vec::new();
+    Opml::from_manifest(&manifest).write(&mut opml)?;
+    std::fs::write(args.output_dir.join(opml::OPML_FILE), opml)?;
+
+    // Only the RSS feeds link to the hubs, so the other formats aren't published to them.
+    let changed_feed_urls: Vec<String> = collected
+        .changed_feeds
+        .iter()
+        .filter(|path| path.ends_with(FeedFormat::Rss.file_name()))
+        .filter_map(|path| public_url(&args, path))
+        .collect();
+    websub::publish_changes(&args.websub_hubs, &changed_feed_urls);
+    webhook::notify(&config.webhooks, &collected.new_episodes);
+
+    if args.strict && !collected.invalid_feeds.is_empty() {
+        let invalid_feeds: Vec<String> = collected.invalid_feeds.iter().map(|path| path.display().to_string()).collect();
+        return Err(format!("feeds failed validation: {}", invalid_feeds.join(", ")).into());
+    }
+    Ok(())
+}
+
+/// Fetches a program and its episodes and writes its feed.
+fn write_program_feed(
+    args: &Args,
+    config: &Config,
+    client: &airnet::AirnetClient,
+    archive: Option<&Archive>,
+    program: &str,
+    out_path: &Path,
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let now = collected.started_at;
+    let published = PublishedGuids::read(out_path)?;
+    if args.use_custom_rss_serialization {
+        let rss_feed = generate_rss_feed(&args.airnet_url, program, |program, episodes| {
+            let live_item = args.live_stream_url.as_deref().and_then(|stream_url| {
+                live::airing_episode(&episodes, now).map(|episode| live::live_item(&program, episode, stream_url))
+            });
+            let episodes = prepare_episodes(args, client, archive, &program, episodes, collected)?;
+            let genre = genre::genre_of(&config.genres, &program);
+            let mut rss_feed = convert_to_rss_v2(program, episodes)?;
+            if let Some(live_item) = live_item {
+                rss_feed.channel_mut().set_live_item(live_item);
+            }
+            if let Some(genre) = genre {
+                genre.categorize(config.genre_domain(), &mut rss_feed);
+            }
+            Ok(rss_feed)
+        })?;
+        let channel = rss_feed.channel();
+        collected.new_episodes.extend(
+            channel
+                .items()
+                .iter()
+                .filter(|item| published.is_new(item))
+                .map(|item| EpisodePublished::from_item(program, channel.title(), item)),
+        );
+        write_feed(args, out_path, rss_feed, collected)?;
+    } else {
+        let mut rss_feed = generate_rss_feed(&args.airnet_url, program, |program, episodes| {
+            let episodes = prepare_episodes(args, client, archive, &program, episodes, collected)?;
+            convert_to_rss(program, episodes)
+        })?;
+        collected.new_episodes.extend(
+            rss_feed
+                .items
+                .iter()
+                .filter(|item| published.is_new_legacy(item))
+                .map(|item| EpisodePublished::from_legacy_item(program, &rss_feed.title, item)),
+        );
+        published.apply_legacy(&mut rss_feed);
+        if let Some(feed_url) = public_url(args, out_path) {
+            rss_feed = rss_feed.atom_link(feed_url);
+        }
+        let stylesheet = feed_stylesheet(args, out_path);
+        let writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+        let r: Result<_, std::io::Error> =
+            macro_generate_rss_custom!(writer, rss_feed, stylesheet.as_deref()).map(|writer| writer.into_inner());
+        let feed = r?;
+        collected.write_if_changed(out_path, &feed)?;
+        collected.validate_feed(out_path)?;
+        if !args.extra_formats.is_empty() {
+            let rss_feed = Rss::from_xml(std::str::from_utf8(&feed)?)?;
+            write_extra_formats(args, out_path, &rss_feed, collected)?;
+        }
+    }
+    Ok(())
+}
+
+/// Finishes the feed of a program that has ended, keeping the episodes it
+/// last had. Feeds written before now are read back; otherwise the feed is
+/// made from what Airnet still has of the program. Archived programs are
+/// still on Airnet, so they're fetched for the site to keep listing them.
+fn retire_program(
+    args: &Args,
+    program: &str,
+    out_path: &Path,
+    retirement: Retirement,
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Retiring {program}: {}", retirement.describe());
+    let fetched = match retirement {
+        Retirement::Archived => collected.fetch_programs(&args.airnet_url, &[program.to_owned()]),
+        Retirement::NotFound => Err(format!("{program} is no longer on Airnet and has no previous feed").into()),
+    };
+    let mut rss_feed = match std::fs::read_to_string(out_path) {
+        Ok(previous) => Rss::from_xml(&previous)?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            fetched?;
+            let (details, episodes) = collected
+                .programs
+                .iter()
+                .find(|(details, _)| details.slug == program)
+                .ok_or_else(|| format!("{program} was not fetched"))?;
+            convert_to_rss_v2(details.clone(), episodes.clone())?
+        }
+        Err(err) => return Err(err.into()),
+    };
+    retire::retire_feed(&mut rss_feed, collected.started_at, args.retirement_item);
+    if let Some(retired_at) = retire::retired_at(&rss_feed) {
+        collected.retired.insert(program.to_owned(), retired_at);
+    }
+    write_feed(args, out_path, rss_feed, collected)
+}
+
+fn write_artist_feeds(
+    args: &Args,
+    client: &airnet::AirnetClient,
+    artist_feeds: &ArtistFeeds,
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    collected.fetch_programs(&args.airnet_url, &artist_feeds.programs)?;
+    collected.fetch_playlists(client, |slug| artist_feeds.includes_program(slug))?;
+
+    let mut written: BTreeMap<String, String> = BTreeMap::new();
+    for artist in artist_feeds.artists(&collected.programs, &collected.playlists) {
+        let slug = slugify(&artist);
+        if slug.is_empty() {
+            eprintln!("Skipping the feed of artist {artist:?}, whose name has no letters or digits for its path");
+            continue;
+        }
+        if let Some(first) = written.get(&slug) {
+            eprintln!("Skipping the feed of artist {artist:?}, whose path {slug:?} is already used by {first:?}");
+            continue;
+        }
+        written.insert(slug.clone(), artist.clone());
+        let rss_feed = artist_feeds.generate(&artist, &collected.programs, &collected.playlists);
+        let artist_dir = args.output_dir.join("pbsfm/").join(artist_feed::ARTIST_FEEDS_DIR).join(slug);
+        std::fs::create_dir_all(&artist_dir)?;
+        write_feed(args, &artist_dir.join("rss.xml"), rss_feed, collected)?;
+    }
+    Ok(())
+}
+
+/// Writes a stub feed at the old slug pointing podcast apps to the new
+/// program's feed, and a page sending browsers there.
+fn write_redirect(
+    args: &Args,
+    old_slug: &str,
+    new_slug: &str,
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let new_feed_path = args.output_dir.join("pbsfm/").join(new_slug).join("rss.xml");
+    let new_feed_url = public_url(args, &new_feed_path)
+        .ok_or("redirects need --public-base-url, as itunes:new-feed-url takes an absolute URL")?;
+    collected.fetch_programs(&args.airnet_url, &[new_slug.to_owned()])?;
+    let (program, _) = collected
+        .programs
+        .iter()
+        .find(|(program, _)| program.slug == new_slug)
+        .ok_or_else(|| format!("{new_slug} was not fetched"))?;
+
+    let old_dir = args.output_dir.join("pbsfm/").join(old_slug);
+    std::fs::create_dir_all(&old_dir)?;
+    std::fs::write(
+        old_dir.join(redirect::REDIRECT_PAGE_FILE),
+        redirect::redirect_page(&program.name, &new_feed_url),
+    )?;
+    let rss_feed = redirect::stub_feed(program.clone(), &new_feed_url)?;
+    write_feed(args, &old_dir.join("rss.xml"), rss_feed, collected)
+}
+
+/// Fetches the playlists of the program feeds' episodes and writes the site's pages.
+fn write_site(
+    args: &Args,
+    client: &airnet::AirnetClient,
+    site_dir: &Path,
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    collected.fetch_playlists(client, |slug| args.programs.iter().any(|program| program == slug))?;
+    let programs: Vec<(ProgramDetails, Vec<Episode>)> = collected
+        .programs
+        .iter()
+        .filter(|(program, _)| args.programs.contains(&program.slug))
+        .cloned()
+        .collect();
+    let site = Site::new(site_feed_links(args, site_dir)?);
+    if args.site_program_pages_only {
+        site.write_program_pages(site_dir, &programs, &collected.playlists, &collected.retired)
+    } else {
+        site.write(site_dir, &programs, &collected.playlists, &collected.retired)
+    }
+}
+
+/// Links to the feeds by their public URL when it's known, otherwise
+/// relative to the site, which then has to contain the output directory.
+fn site_feed_links(args: &Args, site_dir: &Path) -> Result<FeedLinks, Box<dyn std::error::Error>> {
+    if let Some(base_url) = &args.public_base_url {
+        return Ok(FeedLinks::Public(base_url.clone()));
+    }
+    let feeds_dir = args.output_dir.strip_prefix(site_dir).map_err(|_| {
+        format!(
+            "--output-dir {} is outside --site-dir {}, so --public-base-url is needed to link to the feeds",
+            args.output_dir.display(),
+            site_dir.display()
+        )
+    })?;
+    let segments: Vec<String> = feeds_dir
+        .components()
+        .map(|component| format!("{}/", component.as_os_str().to_string_lossy()))
+        .collect();
+    Ok(FeedLinks::Relative(segments.concat()))
+}
+
+/// Adds the changes to the station's programs since the last run to the
+/// changelog feed and saves the program list for the next run to compare with.
+fn write_changelog_feed(
+    args: &Args,
+    all_programs: &[ProgramDescription],
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let changelog_dir = args.output_dir.join("pbsfm/").join(changelog::CHANGELOG_SLUG);
+    std::fs::create_dir_all(&changelog_dir)?;
+    let snapshot_path = changelog_dir.join(changelog::SNAPSHOT_FILE);
+    let out_path = changelog_dir.join("rss.xml");
+
+    let changes = match changelog::read_snapshot(&snapshot_path)? {
+        Some(previous) => changelog::diff(&previous, all_programs),
+        None => Vec::new(),
+    };
+    let previous = match std::fs::read_to_string(&out_path) {
+        Ok(previous) => Some(Rss::from_xml(&previous)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+    let rss_feed = changelog::changelog_feed(previous.as_ref(), &changes, collected.started_at);
+    write_feed(args, &out_path, rss_feed, collected)?;
+    std::fs::write(snapshot_path, serde_json::to_vec_pretty(all_programs)?)?;
+    Ok(())
+}
+
+/// Fetches any of the smart feed's programs (and playlists, if its filter
+/// needs them) not already fetched for the per-program feeds, then writes it.
+fn write_smart_feed(
+    args: &Args,
+    client: &airnet::AirnetClient,
+    smart_feed: &SmartFeed,
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    collected.fetch_programs(&args.airnet_url, &smart_feed.programs)?;
+    if smart_feed.filter.needs_playlist() {
+        collected.fetch_playlists(client, |slug| smart_feed.includes_program(slug))?;
+    }
+
+    let rss_feed = smart_feed.generate(&collected.programs, &collected.playlists);
+    let smart_feed_dir = args.output_dir.join("pbsfm/").join(smart_feed::SMART_FEEDS_DIR).join(&smart_feed.slug);
+    std::fs::create_dir_all(&smart_feed_dir)?;
+    write_feed(args, &smart_feed_dir.join("rss.xml"), rss_feed, collected)?;
+    Ok(())
+}
+
+/// Writes the feed, keeping the guids of the items it had already published
+/// and linking it to its canonical URL when the public base URL is known, and
+/// validates what was written. Then writes it in any extra formats.
+fn write_feed(
+    args: &Args,
+    out_path: &Path,
+    mut rss_feed: Rss,
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    PublishedGuids::read(out_path)?.apply(&mut rss_feed);
+    if let Some(feed_url) = public_url(args, out_path) {
+        websub::add_links(&mut rss_feed, &feed_url, &args.websub_hubs);
+    }
+    let mut feed = Vec::new();
+    write_rss_v2(&mut feed, &rss_feed, feed_stylesheet(args, out_path).as_deref())?;
+    collected.write_if_changed(out_path, &feed)?;
+    collected.validate_feed(out_path)?;
+    write_extra_formats(args, out_path, &rss_feed, collected)
+}
+
+/// Writes the feed in each of the extra formats, next to its `rss.xml`.
+fn write_extra_formats(
+    args: &Args,
+    out_path: &Path,
+    rss_feed: &Rss,
+    collected: &mut Collected,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for format in &args.extra_formats {
+        if *format != FeedFormat::Rss {
+            let path = out_path.with_file_name(format.file_name());
+            let feed = format.render(rss_feed, public_url(args, &path).as_deref(), collected.started_at)?;
+            collected.write_if_changed(&path, &feed)?;
         }
     }
     Ok(())
 }
+
+/// The URL a file in the output directory is served from, when the public base URL is known.
+pub(crate) fn public_url(args: &Args, path: &Path) -> Option<String> {
+    let base_url = args.public_base_url.as_deref()?;
+    let relative = path.strip_prefix(&args.output_dir).ok()?;
+    let segments: Vec<String> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(format!("{}/{}", base_url.trim_end_matches('/'), segments.join("/")))
+}
+
+/// The href of the stylesheet linked from the feed at `out_path`, with `--feed-stylesheet`.
+fn feed_stylesheet(args: &Args, out_path: &Path) -> Option<String> {
+    if !args.feed_stylesheet {
+        return None;
+    }
+    let relative = out_path.strip_prefix(&args.output_dir).ok()?;
+    Some(site::feed_stylesheet_href(relative))
+}
+
+/// The content of an `<?xml-stylesheet?>` processing instruction for an XSL stylesheet.
+pub fn xml_stylesheet_instruction(href: &str) -> String {
+    format!(r#"xml-stylesheet type="text/xsl" href="{}""#, quick_xml::escape::escape(href))
+}
+
+/// The public URL of a file in the output directory, or its path relative to
+/// the output directory when the public base URL isn't known.
+fn feed_href(args: &Args, path: &Path) -> String {
+    public_url(args, path).unwrap_or_else(|| {
+        let relative = path.strip_prefix(&args.output_dir).unwrap_or(path);
+        let segments: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        segments.join("/")
+    })
+}
+
+/// Writes the feed, preceded by an `<?xml-stylesheet?>` instruction when
+/// given a stylesheet href.
+pub(crate) fn write_rss_v2<W: Write>(
+    out: W,
+    rss_feed: &Rss,
+    stylesheet: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = Writer::new_with_indent(out, b' ', 2);
+
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+    if let Some(href) = stylesheet {
+        writer.write_event(Event::PI(BytesPI::new(xml_stylesheet_instruction(href))))?;
+    }
+    writer.write_serializable("rss", rss_feed)?;
+    Ok(())
+}
+
+/// Records the fetched episodes in the archive, if any, swapping them for the
+/// full archived history when the feed is generated from the archive, and
+/// leaves out episodes that haven't finished airing. Then fetches playlists
+/// and computes content statistics when either the item descriptions or the
+/// stats report need them, and keeps a copy of the episodes for the
+/// station-wide feeds.
+fn prepare_episodes(
+    args: &Args,
+    client: &airnet::AirnetClient,
+    archive: Option<&Archive>,
+    program: &ProgramDetails,
+    episodes: Vec<Episode>,
+    collected: &mut Collected,
+) -> Result<Vec<Episode>, Box<dyn std::error::Error>> {
+    let mut episodes = episodes;
+    if let Some(archive) = archive {
+        archive.record_program(program, collected.started_at)?;
+        archive.record_episodes(&program.slug, &episodes, collected.started_at)?;
+        if args.feed_from_archive {
+            episodes = archive.episodes(&program.slug)?;
+            for episode in &episodes {
+                let playlist = archive.playlist(&program.slug, &episode.start)?;
+                if !playlist.is_empty() {
+                    collected.playlists.insert(&program.slug, &episode.start, playlist);
+                }
+            }
+        }
+    }
+    episodes = live::aired_episodes(episodes, collected.started_at);
+    if args.content_stats_in_description || args.stats_format.is_some() {
+        let stats = ProgramStats::fetch(client, &mut collected.playlists, PBSFM_STATION, program, &episodes)?;
+        if args.content_stats_in_description {
+            episodes = stats.annotate_descriptions(episodes);
+        }
+        collected.program_stats.push(stats);
+    }
+    collected.programs.push((program.clone(), episodes.clone()));
+    Ok(episodes)
+}
+
 pub fn generate_rss_feed<RSS>(
     airnet_url: &str,
     program_name: &str,
-    generate_rss_f: impl Fn(ProgramDetails, Vec<Episode>) -> Result<RSS, Box<dyn std::error::Error>>,
+    generate_rss_f: impl FnOnce(ProgramDetails, Vec<Episode>) -> Result<RSS, Box<dyn std::error::Error>>,
 ) -> Result<RSS, Box<dyn std::error::Error>> {
     let client = airnet::AirnetClient::new(airnet_url.to_owned());
     let program = client.program(PBSFM_STATION, program_name)?;
@@ -75,6 +850,7 @@ pub fn convert_to_rss_v2(
     episodes: Vec<Episode>,
 ) -> Result<Rss, Box<dyn std::error::Error>> {
     let program_link = format!("https://www.pbsfm.org.au/program/{}", program.slug);
+    let artwork = rm_query_params(&program.profile_image_url)?;
 
     let rss_data = Rss::new(
         ChannelBuilder::new(
@@ -82,16 +858,18 @@ pub fn convert_to_rss_v2(
             &program_link,
             &program.description
         )
-            .category(CategoryBuilder::new(
+            .category(vec![CategoryBuilder::new(
                 program.grid_description.as_ref().unwrap_or(&"".to_owned())
-            ).build())
+            ).build()])
             .image(
                 ImageBuilder::new(
-                    rm_query_params(&program.profile_image_url)?,
+                    &artwork,
                     &program.name,
                     &program_link,
                 ).build()
             )
+            .itunes_image(ItunesImage::new(artwork))
+            .itunes_explicit(false)
             .language("en".to_owned())
             .item(convert_to_items_v2(&program, episodes))
             .build()
@@ -104,39 +882,70 @@ fn convert_to_items_v2(
 ) -> Vec<Item> {
     episodes.iter().map( |episode| {
         println!("Writing episode: {:?}, {}", episode.title, episode.start);
+        episode_item_builder(program, episode).build()
+    }).collect()
+}
 
-        let default_title = format!("Untitled - {}", episode.start.format("%Y-%m-%d"));
-
-        let title = episode
-            .title
-            .as_ref()
-            .unwrap_or(&default_title);
-        let episode_link = format!(
-            "https://www.pbsfm.org.au/program/{}/{}/{}",
-            program.slug,
-            episode.start.format("%Y-%m-%d"),
-            episode.start.format("%H-%M-%S")
-        );
+pub(crate) fn episode_title(episode: &Episode) -> String {
+    episode.title.clone().unwrap_or_else(|| {
+        if episode.multiple_eps_on_day {
+            format!("Untitled - {}", episode.start.format("%Y-%m-%d %H:%M"))
+        } else {
+            format!("Untitled - {}", episode.start.format("%Y-%m-%d"))
+        }
+    })
+}
 
-        ItemBuilder::with_title(title)
-            .guid(ItemGuidBuilder::new(&episode_link).build())
-            .link(episode_link)
-            .description(episode.description.clone())
-            .author(program.broadcasters.clone())
-            .enclosure(
-                Enclosure::new(
-                    format!(
-                        "https://airnet.org.au/omnystudio/3pbs/{}/{}/aac_mid.m4a",
-                        program.slug,
-                        episode.start.format("%Y-%m-%d+%H:%M:%S")
-                    ),
-                    None,
-                    "audio/mp4"
-                )
-            )
-            .pub_date(episode.start.date().to_string())
-            .build()
-    }).collect()
+/// The episode's page on pbsfm.org.au.
+pub fn episode_link(program_slug: &str, start: &NaiveDateTime) -> String {
+    format!(
+        "https://www.pbsfm.org.au/program/{}/{}/{}",
+        program_slug,
+        start.format("%Y-%m-%d"),
+        start.format("%H-%M-%S")
+    )
+}
+
+/// The episode's audio, as published by Airnet's Omny Studio integration.
+pub fn enclosure_url(program_slug: &str, start: &NaiveDateTime) -> String {
+    format!(
+        "https://airnet.org.au/omnystudio/3pbs/{}/{}/aac_mid.m4a",
+        program_slug,
+        start.format("%Y-%m-%d+%H:%M:%S")
+    )
+}
+
+/// The RFC 822 date of an episode start.
+pub fn pub_date(start: &NaiveDateTime) -> String {
+    station_datetime(start).to_rfc2822()
+}
+
+/// An Airnet episode time, which is Melbourne local time.
+pub(crate) fn station_datetime(time: &NaiveDateTime) -> DateTime<chrono_tz::Tz> {
+    Melbourne
+        .from_local_datetime(time)
+        .earliest()
+        // Times skipped by the change to daylight saving are read as standard time.
+        .unwrap_or_else(|| Melbourne.from_utc_datetime(&(*time - TimeDelta::hours(10))))
+}
+
+pub(crate) fn episode_item_builder(program: &ProgramDetails, episode: &Episode) -> ItemBuilder {
+    let episode_link = episode_link(&program.slug, &episode.start);
+
+    ItemBuilder::with_title(episode_title(episode))
+        .guid(guid::episode_guid(episode))
+        .link(episode_link)
+        .description(episode.description.clone())
+        .author(program.broadcasters.clone())
+        .podcast_person(
+            presenter::parse_presenters(&program.broadcasters).into_iter().map(PodcastPerson::host).collect::<Vec<_>>()
+        )
+        .enclosure(
+            Enclosure::new(enclosure_url(&program.slug, &episode.start), None, "audio/mp4")
+        )
+        .pub_date(pub_date(&episode.start))
+        .itunes_duration(episode.duration)
+        .to_owned()
 }
 
 pub fn convert_to_rss(
@@ -154,36 +963,39 @@ pub fn convert_to_rss(
 
     for episode in episodes {
         println!("Writing episode: {:?}, {}", episode.title, episode.start);
-        let title = &episode
-            .title
-            .unwrap_or(format!("Untitled - {}", episode.start.format("%Y-%m-%d")));
-        let episode_link = format!(
-            "https://www.pbsfm.org.au/program/{}/{}/{}",
-            program.slug,
-            episode.start.format("%Y-%m-%d"),
-            episode.start.format("%H-%M-%S")
-        );
+        let title = &episode_title(&episode);
 
         rss_data.add_item(
             RssItem::new()
                 .title(title)
-                .link(&episode_link)
-                .guid(&episode_link)
+                .link(episode_link(&program.slug, &episode.start))
+                .guid(&episode.episode_rest_url)
                 .author(&program.broadcasters)
                 .description(episode.description.unwrap_or_default())
-                .enclosure(format!(
-                    "https://airnet.org.au/omnystudio/3pbs/{}/{}/aac_mid.m4a",
-                    program.slug,
-                    episode.start.format("%Y-%m-%d+%H:%M:%S")
-                ))
-                .pub_date(episode.start.date().to_string()),
+                .enclosure(enclosure_url(&program.slug, &episode.start))
+                .pub_date(pub_date(&episode.start)),
         );
     }
 
     Ok(rss_data)
 }
 
-fn rm_query_params(url: &str) -> Result<String,Box<dyn std::error::Error>> {
+/// Lower-cases `name` and joins its words with hyphens, e.g. "Paul Grabowsky" -> "paul-grabowsky".
+pub fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Whether `slug` is only lower-case ASCII letters, digits and hyphens, so is
+/// safe as a path segment.
+pub(crate) fn is_slug(slug: &str) -> bool {
+    !slug.is_empty() && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+pub(crate) fn rm_query_params(url: &str) -> Result<String,Box<dyn std::error::Error>> {
     let reg = Regex::new(r"\?.*$")?;
     Ok(reg.replace(url, "").to_string())
 }
\ No newline at end of file
@@ -0,0 +1,103 @@
+use crate::airnet::types::{Episode, ProgramDetails};
+use crate::rss::{Enclosure, ItemGuidBuilder, LiveItem, LiveItemBuilder, LiveItemStatus};
+use crate::{episode_link, episode_title, station_datetime};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono_tz::Australia::Melbourne;
+
+/// `now` in Melbourne local time, which is how Airnet gives episode times.
+pub fn station_time(now: DateTime<Utc>) -> NaiveDateTime {
+    now.with_timezone(&Melbourne).naive_local()
+}
+
+/// Whether the episode has finished airing, and so has audio to download.
+pub fn has_aired(episode: &Episode, now: DateTime<Utc>) -> bool {
+    episode.end <= station_time(now)
+}
+
+/// Drops episodes still airing or yet to air. They are picked up by the
+/// first run after they end.
+pub fn aired_episodes(episodes: Vec<Episode>, now: DateTime<Utc>) -> Vec<Episode> {
+    episodes
+        .into_iter()
+        .filter(|episode| {
+            let aired = has_aired(episode, now);
+            if !aired {
+                println!("Deferring episode until it has aired: {:?}, {}", episode.title, episode.start);
+            }
+            aired
+        })
+        .collect()
+}
+
+/// The episode on air at `now`, preferring the one Airnet marks as current.
+pub fn airing_episode(episodes: &[Episode], now: DateTime<Utc>) -> Option<&Episode> {
+    let now = station_time(now);
+    let mut airing = episodes.iter().filter(|episode| episode.start <= now && now < episode.end);
+    let first = airing.clone().next();
+    airing.find(|episode| episode.current_episode).or(first)
+}
+
+/// A `podcast:liveItem` pointing at the station's live stream for the episode on air.
+pub fn live_item(program: &ProgramDetails, episode: &Episode, stream_url: &str) -> LiveItem {
+    let link = episode_link(&program.slug, &episode.start);
+    LiveItemBuilder::new(
+        LiveItemStatus::Live,
+        station_datetime(&episode.start).to_rfc3339(),
+        station_datetime(&episode.end).to_rfc3339(),
+        Enclosure::new(stream_url, Some(0), stream_media_type(stream_url)),
+    )
+    .title(episode_title(episode))
+    .guid(ItemGuidBuilder::new(format!("{link}#live")).is_permalink(false).build())
+    .link(link)
+    .description(episode.description.clone())
+    .author(program.broadcasters.clone())
+    .build()
+}
+
+fn stream_media_type(stream_url: &str) -> &'static str {
+    let path = stream_url.split(['?', '#']).next().unwrap_or_default();
+    if path.ends_with(".aac") {
+        "audio/aac"
+    } else if path.ends_with(".m4a") {
+        "audio/mp4"
+    } else {
+        "audio/mpeg"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::TimeZone;
+
+    fn episode(day: u32, current_episode: bool) -> Episode {
+        Episode { current_episode, ..test_support::episode(test_support::datetime(8, day, 11)) }
+    }
+
+    /// 12:00 in Melbourne (AEST) on the given day in August 2025.
+    fn noon(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 8, day, 2, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_aired_episodes() {
+        let episodes = vec![episode(18, false), episode(25, true)];
+        assert_eq!(aired_episodes(episodes.clone(), noon(25)), vec![episode(18, false)]);
+        assert_eq!(aired_episodes(episodes.clone(), noon(26)), episodes);
+    }
+
+    #[test]
+    fn test_airing_episode() {
+        let episodes = vec![episode(18, false), episode(25, true)];
+        assert_eq!(airing_episode(&episodes, noon(25)), Some(&episodes[1]));
+        assert_eq!(airing_episode(&episodes, noon(26)), None);
+    }
+
+    #[test]
+    fn test_stream_media_type() {
+        assert_eq!(stream_media_type("https://stream.example/3pbs.aac?listener=1"), "audio/aac");
+        assert_eq!(stream_media_type("https://stream.example/3pbs.m4a"), "audio/mp4");
+        assert_eq!(stream_media_type("https://stream.example/3pbs"), "audio/mpeg");
+    }
+}
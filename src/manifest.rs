@@ -0,0 +1,156 @@
+use crate::aggregate::COMBINED_FEED_SLUG;
+use crate::atom::item_date;
+use crate::changelog::CHANGELOG_SLUG;
+use crate::feed_format::FeedFormat;
+use crate::retire;
+use crate::rss::Rss;
+use chrono::SecondsFormat;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File, in the output directory, listing every program feed.
+pub const MANIFEST_FILE: &str = "index.json";
+
+/// Every program feed in the output directory, so that the site, monitoring
+/// and directories can read one file instead of crawling each feed.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Manifest {
+    pub programs: Vec<ManifestProgram>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ManifestProgram {
+    pub slug: String,
+    pub title: String,
+    /// The program's page on pbsfm.org.au.
+    pub link: String,
+    pub category: Option<String>,
+    pub artwork_url: Option<String>,
+    /// The URL of the program's feed in each format it's written in, keyed by format name.
+    pub feeds: BTreeMap<&'static str, String>,
+    pub item_count: usize,
+    /// RFC 3339 air time of the newest episode.
+    pub newest_episode: Option<String>,
+    /// `sha256:` followed by the hex SHA-256 of the RSS feed, which changes whenever the feed does.
+    pub content_hash: String,
+    /// RFC 3339 time the program's feed was retired, once it has ended.
+    pub retired: Option<String>,
+}
+
+impl ManifestProgram {
+    /// Describes the program from its RSS feed, `feed` being the feed's bytes as written.
+    pub fn from_feed(slug: &str, rss: &Rss, feed: &[u8], feeds: BTreeMap<&'static str, String>) -> Self {
+        let channel = rss.channel();
+        ManifestProgram {
+            slug: slug.to_owned(),
+            title: channel.title().to_owned(),
+            link: channel.link().to_owned(),
+            category: channel.category().map(|category| category.text().to_owned()).filter(|text| !text.is_empty()),
+            artwork_url: channel
+                .itunes_image()
+                .map(|artwork| artwork.href().to_owned())
+                .or(channel.image().map(|image| image.url().to_owned())),
+            feeds,
+            item_count: channel.items().len(),
+            newest_episode: channel
+                .items()
+                .iter()
+                .filter_map(item_date)
+                .max()
+                .map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, false)),
+            content_hash: content_hash(feed),
+            retired: retire::retired_at(rss),
+        }
+    }
+}
+
+impl Manifest {
+    /// Reads every program feed under `pbsfm/` in the output directory, so that
+    /// the manifest also covers programs not refreshed by this run. `feed_url`
+    /// gives the URL of a feed file.
+    pub fn read(output_dir: &Path, feed_url: impl Fn(&Path) -> String) -> Result<Self, Box<dyn std::error::Error>> {
+        let station_dir = output_dir.join("pbsfm");
+        let mut slugs: Vec<String> = match std::fs::read_dir(&station_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|slug| slug != COMBINED_FEED_SLUG && slug != CHANGELOG_SLUG)
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        slugs.sort();
+
+        let mut programs = Vec::new();
+        for slug in slugs {
+            let program_dir = station_dir.join(&slug);
+            let Ok(feed) = std::fs::read(program_dir.join(FeedFormat::Rss.file_name())) else {
+                continue;
+            };
+            let rss = Rss::from_xml(std::str::from_utf8(&feed)?)?;
+            if is_moved(&rss) {
+                continue;
+            }
+            let feeds = FeedFormat::ALL
+                .into_iter()
+                .map(|format| (format, program_dir.join(format.file_name())))
+                .filter(|(_, path)| path.exists())
+                .map(|(format, path)| (format.name(), feed_url(&path)))
+                .collect();
+            programs.push(ManifestProgram::from_feed(&slug, &rss, &feed, feeds));
+        }
+        Ok(Manifest { programs })
+    }
+}
+
+/// Whether the feed is a stub left at a program's old slug, pointing to
+/// another feed rather than to itself.
+fn is_moved(rss: &Rss) -> bool {
+    let channel = rss.channel();
+    channel.itunes_new_feed_url().is_some_and(|new_feed_url| channel.self_link() != Some(new_feed_url))
+}
+
+/// `sha256:` followed by the hex SHA-256 of the content.
+pub fn content_hash(content: &[u8]) -> String {
+    let digest: String = Sha256::digest(content).iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("sha256:{digest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::{CategoryBuilder, ChannelBuilder, ItemBuilder, ItunesImage};
+
+    #[test]
+    fn test_from_feed() {
+        let rss = Rss::new(
+            ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "Jazz")
+                .category(vec![CategoryBuilder::new("Groovin' jazz").build()])
+                .itunes_image(ItunesImage::new("https://airnet/black-wax.jpg"))
+                .item(vec![
+                    ItemBuilder::with_title("Older").pub_date("Mon, 16 Jun 2025 11:00:00 +1000".to_owned()).build(),
+                    ItemBuilder::with_title("Newer").pub_date("Mon, 25 Aug 2025 11:00:00 +1000".to_owned()).build(),
+                ])
+                .build(),
+        );
+        let feeds = BTreeMap::from([("rss", "pbsfm/black-wax/rss.xml".to_owned())]);
+        let program = ManifestProgram::from_feed("black-wax", &rss, b"", feeds.clone());
+        assert_eq!(
+            program,
+            ManifestProgram {
+                slug: "black-wax".to_owned(),
+                title: "Black Wax".to_owned(),
+                link: "https://www.pbsfm.org.au/program/black-wax".to_owned(),
+                category: Some("Groovin' jazz".to_owned()),
+                artwork_url: Some("https://airnet/black-wax.jpg".to_owned()),
+                feeds,
+                item_count: 2,
+                newest_episode: Some("2025-08-25T11:00:00+10:00".to_owned()),
+                content_hash: "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_owned(),
+                retired: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::feed_format::FeedFormat;
+use crate::manifest::Manifest;
+use crate::site::SITE_TITLE;
+use quick_xml::events::{BytesDecl, Event};
+use quick_xml::writer::Writer;
+use serde::Serialize;
+use std::io::Write;
+
+/// File, in the output directory, listing every program feed for podcast apps to import.
+pub const OPML_FILE: &str = "index.opml";
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Opml {
+    #[serde(rename = "@version")]
+    version: &'static str,
+    head: OpmlHead,
+    body: OpmlBody,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct OpmlHead {
+    title: &'static str,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct OpmlBody {
+    outline: Vec<Outline>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct Outline {
+    #[serde(rename = "@type")]
+    outline_type: &'static str,
+    #[serde(rename = "@text")]
+    text: String,
+    #[serde(rename = "@title")]
+    title: String,
+    #[serde(rename = "@xmlUrl")]
+    xml_url: String,
+    #[serde(rename = "@htmlUrl")]
+    html_url: String,
+}
+
+impl Opml {
+    /// Subscribes to the RSS feed of each program in the manifest, by its canonical URL.
+    pub fn from_manifest(manifest: &Manifest) -> Self {
+        let outline = manifest
+            .programs
+            .iter()
+            .filter_map(|program| {
+                Some(Outline {
+                    outline_type: "rss",
+                    text: program.title.clone(),
+                    title: program.title.clone(),
+                    xml_url: program.feeds.get(FeedFormat::Rss.name())?.clone(),
+                    html_url: program.link.clone(),
+                })
+            })
+            .collect();
+        Opml { version: "2.0", head: OpmlHead { title: SITE_TITLE }, body: OpmlBody { outline } }
+    }
+
+    pub fn write<W: Write>(&self, out: W) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = Writer::new_with_indent(out, b' ', 2);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+        writer.write_serializable("opml", self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::ManifestProgram;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_write() {
+        let manifest = Manifest {
+            programs: vec![ManifestProgram {
+                slug: "black-wax".to_owned(),
+                title: "Black Wax".to_owned(),
+                link: "https://www.pbsfm.org.au/program/black-wax".to_owned(),
+                category: None,
+                artwork_url: None,
+                feeds: BTreeMap::from([("rss", "https://example.com/feeds/pbsfm/black-wax/rss.xml".to_owned())]),
+                item_count: 0,
+                newest_episode: None,
+                content_hash: String::new(),
+                retired: None,
+            }],
+        };
+        let mut opml = Vec::new();
+        Opml::from_manifest(&manifest).write(&mut opml).unwrap();
+        assert_eq!(
+            String::from_utf8(opml).unwrap(),
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<opml version="2.0">
+  <head>
+    <title>PBS radio RSS feeds</title>
+  </head>
+  <body>
+    <outline type="rss" text="Black Wax" title="Black Wax" xmlUrl="https://example.com/feeds/pbsfm/black-wax/rss.xml" htmlUrl="https://www.pbsfm.org.au/program/black-wax"/>
+  </body>
+</opml>"#
+        );
+    }
+}
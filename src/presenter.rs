@@ -0,0 +1,115 @@
+use crate::aggregate::{aggregate_feed, program_episodes};
+use crate::airnet::types::{Episode, ProgramDetails};
+use crate::rss::{ChannelBuilder, Rss};
+use crate::slugify;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Output directory of the presenter feeds, e.g. `pbsfm/presenters/adam-rudegeair/rss.xml`.
+pub const PRESENTER_FEEDS_DIR: &str = "presenters";
+
+static SEPARATORS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\s*(?:,|&|\band\b)\s*").expect("The separator pattern is valid."));
+
+/// The presenters named in a program's free-text `broadcasters`, in order
+/// and without repeats. Names are separated by commas, "&" or the word
+/// "and", as in "Vince Peach, Jane Doe and John Smith".
+pub fn parse_presenters(broadcasters: &str) -> Vec<String> {
+    let mut presenters: Vec<String> = Vec::new();
+    for name in SEPARATORS.split(broadcasters).map(str::trim).filter(|name| !name.is_empty()) {
+        if !presenters.iter().any(|presenter| presenter.eq_ignore_ascii_case(name)) {
+            presenters.push(name.to_owned());
+        }
+    }
+    presenters
+}
+
+/// Every presenter of the programs, with their slug, sorted by slug.
+/// Presenters whose names only differ in case or punctuation share a feed.
+pub fn presenters(programs: &[(ProgramDetails, Vec<Episode>)]) -> Vec<(String, String)> {
+    let mut presenters: Vec<(String, String)> = Vec::new();
+    for (program, _) in programs {
+        for name in parse_presenters(&program.broadcasters) {
+            let slug = slugify(&name);
+            if !slug.is_empty() && !presenters.iter().any(|(existing, _)| *existing == slug) {
+                presenters.push((slug, name));
+            }
+        }
+    }
+    presenters.sort();
+    presenters
+}
+
+/// Builds the feed of the episodes of every program the presenter, by
+/// slug, presents.
+pub fn presenter_feed(name: &str, slug: &str, programs: &[(ProgramDetails, Vec<Episode>)]) -> Rss {
+    let episodes = program_episodes(programs)
+        .into_iter()
+        .filter(|(program, _)| {
+            parse_presenters(&program.broadcasters).iter().any(|presenter| slugify(presenter) == slug)
+        })
+        .collect();
+    aggregate_feed(
+        ChannelBuilder::new(
+            format!("PBS 106.7FM - {name}"),
+            "https://www.pbsfm.org.au",
+            format!("Episodes of every PBS 106.7FM program presented by {name}"),
+        ),
+        episodes,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_parse_presenters() {
+        assert_eq!(parse_presenters("Adam Rudegeair"), vec!["Adam Rudegeair"]);
+        assert_eq!(parse_presenters("Jess Fairfax & Tom Kemp"), vec!["Jess Fairfax", "Tom Kemp"]);
+        assert_eq!(parse_presenters("Jess Fairfax and Tom Kemp"), vec!["Jess Fairfax", "Tom Kemp"]);
+        assert_eq!(
+            parse_presenters("Vince Peach, Jane Doe, and Andy Anderson"),
+            vec!["Vince Peach", "Jane Doe", "Andy Anderson"]
+        );
+        assert_eq!(parse_presenters("Rotating presenters AND guests"), vec!["Rotating presenters", "guests"]);
+        assert_eq!(parse_presenters("Tom Kemp & tom kemp"), vec!["Tom Kemp"]);
+        assert!(parse_presenters(" ").is_empty());
+    }
+
+    fn program(slug: &str, broadcasters: &str) -> ProgramDetails {
+        ProgramDetails { broadcasters: broadcasters.to_owned(), ..test_support::program(slug) }
+    }
+
+    fn episode(program: &str) -> Episode {
+        Episode {
+            title: Some(format!("{program} episode")),
+            episode_rest_url: format!("https://airnet/{program}"),
+            ..test_support::episode(test_support::datetime(8, 25, 11))
+        }
+    }
+
+    #[test]
+    fn test_presenter_feed() {
+        let programs = vec![
+            (program("black-wax", "Adam Rudegeair"), vec![episode("black-wax")]),
+            (program("jazz-spot", "Jane Doe & Adam Rudegeair"), vec![episode("jazz-spot")]),
+            (program("soul-time", "Vince Peach"), vec![episode("soul-time")]),
+        ];
+        assert_eq!(
+            presenters(&programs),
+            vec![
+                ("adam-rudegeair".to_owned(), "Adam Rudegeair".to_owned()),
+                ("jane-doe".to_owned(), "Jane Doe".to_owned()),
+                ("vince-peach".to_owned(), "Vince Peach".to_owned()),
+            ]
+        );
+
+        let rss = presenter_feed("Adam Rudegeair", "adam-rudegeair", &programs);
+        let mut titles: Vec<&str> = rss.channel().items().iter().filter_map(|item| item.title()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["black-wax: black-wax episode", "jazz-spot: jazz-spot episode"]);
+        assert_eq!(rss.channel().title(), "PBS 106.7FM - Adam Rudegeair");
+    }
+}
@@ -0,0 +1,66 @@
+use crate::airnet::types::ProgramDetails;
+use crate::convert_to_rss_v2;
+use crate::rss::Rss;
+use quick_xml::escape::escape;
+
+/// Page written next to a moved program's stub feed, sending browsers to the new feed.
+pub const REDIRECT_PAGE_FILE: &str = "index.html";
+
+/// The feed left at a program's old slug once it has been renamed or merged
+/// into `program`: the new program's channel, with no episodes, telling
+/// podcast apps to move subscribers to `new_feed_url`.
+pub fn stub_feed(program: ProgramDetails, new_feed_url: &str) -> Result<Rss, Box<dyn std::error::Error>> {
+    let description = format!("{} has moved to a new feed: {new_feed_url}", program.name);
+    let mut rss = convert_to_rss_v2(ProgramDetails { description, ..program }, Vec::new())?;
+    rss.channel_mut().set_itunes_new_feed_url(new_feed_url.to_owned());
+    Ok(rss)
+}
+
+/// An HTML page redirecting to `url`, for browsers following links to the old feed.
+pub fn redirect_page(title: &str, url: &str) -> String {
+    let (title, url) = (escape(title), escape(url));
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en-US">
+<head>
+  <meta charset="utf-8">
+  <title>{title} has moved</title>
+  <link rel="canonical" href="{url}">
+  <meta http-equiv="refresh" content="0; url={url}">
+</head>
+<body>
+  <p>{title} has moved to <a href="{url}">{url}</a>.</p>
+</body>
+</html>
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn test_stub_feed() {
+        let program = ProgramDetails {
+            name: "Soul Time".to_owned(),
+            broadcasters: "Vince Peach".to_owned(),
+            description: "Soul".to_owned(),
+            profile_image_url: "https://airnet/soul-time.jpg?w=64".to_owned(),
+            ..test_support::program("soul-time")
+        };
+        let new_feed_url = "https://example.com/feeds/pbsfm/soul-time/rss.xml";
+        let rss = stub_feed(program, new_feed_url).unwrap();
+        assert_eq!(rss.channel().itunes_new_feed_url(), Some(new_feed_url));
+        assert_eq!(rss.channel().description(), format!("Soul Time has moved to a new feed: {new_feed_url}"));
+        assert!(rss.channel().items().is_empty());
+    }
+
+    #[test]
+    fn test_redirect_page() {
+        let page = redirect_page("Rhythm & Soul", "https://example.com/feeds/pbsfm/soul-time/rss.xml");
+        assert!(page.contains(r#"<meta http-equiv="refresh" content="0; url=https://example.com/feeds/pbsfm/soul-time/rss.xml">"#));
+        assert!(page.contains("<title>Rhythm &amp; Soul has moved</title>"));
+    }
+}
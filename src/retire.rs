@@ -0,0 +1,123 @@
+use crate::rss::{ItemBuilder, ItemGuidBuilder, Rss};
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono_tz::Australia::Melbourne;
+
+/// Why a program's feed stopped being updated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retirement {
+    /// Airnet lists the program as archived.
+    Archived,
+    /// Airnet no longer has the program.
+    NotFound,
+}
+
+impl Retirement {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Retirement::Archived => "archived",
+            Retirement::NotFound => "no longer on Airnet",
+        }
+    }
+}
+
+/// Marks the program's last feed as finished: its episodes are kept, with
+/// `itunes:complete` telling podcast apps no more are coming and
+/// `podcast:locked` keeping other platforms from importing it. The
+/// `lastBuildDate` records when it was retired. With `final_item`, an episode
+/// announcing the end of the program is added.
+///
+/// A feed already retired is left as it was, keeping its retirement date.
+pub fn retire_feed(rss: &mut Rss, now: DateTime<Utc>, final_item: bool) {
+    if rss.channel().itunes_complete() {
+        return;
+    }
+    rss.declare_podcast_namespaces();
+    let retired_at = now.with_timezone(&Melbourne).to_rfc2822();
+    let channel = rss.channel_mut();
+    channel.set_itunes_complete();
+    channel.set_podcast_locked();
+    channel.set_last_build_date(retired_at.clone());
+    if final_item {
+        let title = channel.title().to_owned();
+        let link = channel.link().to_owned();
+        channel.add_item(
+            ItemBuilder::with_title(format!("{title} has ended"))
+                .description(format!("{title} is no longer on air. Its past episodes stay in this feed."))
+                .link(link.clone())
+                .guid(ItemGuidBuilder::new(format!("{link}#ended")).is_permalink(false).build())
+                .pub_date(retired_at)
+                .build(),
+        );
+    }
+}
+
+/// RFC 3339 time the feed was retired, if it has been.
+pub fn retired_at(rss: &Rss) -> Option<String> {
+    let channel = rss.channel();
+    if !channel.itunes_complete() {
+        return None;
+    }
+    channel
+        .last_build_date()
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, false))
+}
+
+/// Whether fetching from Airnet failed because it doesn't have what was asked for.
+pub fn is_not_found(err: &(dyn std::error::Error + 'static)) -> bool {
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        .is_some_and(|status| status == reqwest::StatusCode::NOT_FOUND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::ChannelBuilder;
+    use chrono::TimeZone;
+
+    fn black_wax() -> Rss {
+        Rss::new(
+            ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "Jazz")
+                .item(vec![ItemBuilder::with_title("Last episode").build()])
+                .build(),
+        )
+    }
+
+    #[test]
+    fn test_retire_feed() {
+        let mut rss = black_wax();
+        retire_feed(&mut rss, Utc.with_ymd_and_hms(2025, 9, 1, 2, 0, 0).unwrap(), false);
+        let channel = rss.channel();
+        assert!(channel.itunes_complete());
+        assert!(channel.podcast_locked());
+        assert_eq!(channel.last_build_date(), Some("Mon, 1 Sep 2025 12:00:00 +1000"));
+        assert_eq!(channel.items().len(), 1);
+        assert_eq!(retired_at(&rss).as_deref(), Some("2025-09-01T12:00:00+10:00"));
+    }
+
+    #[test]
+    fn test_retire_feed_with_final_item() {
+        let mut rss = black_wax();
+        retire_feed(&mut rss, Utc.with_ymd_and_hms(2025, 9, 1, 2, 0, 0).unwrap(), true);
+        let ended = rss.channel().items().last().unwrap();
+        assert_eq!(ended.title(), Some("Black Wax has ended"));
+        assert_eq!(ended.guid().map(|guid| guid.value()), Some("https://www.pbsfm.org.au/program/black-wax#ended"));
+        assert!(!ended.guid().unwrap().is_permalink());
+        assert_eq!(ended.pub_date(), Some("Mon, 1 Sep 2025 12:00:00 +1000"));
+    }
+
+    #[test]
+    fn test_retire_feed_keeps_first_retirement() {
+        let mut rss = black_wax();
+        retire_feed(&mut rss, Utc.with_ymd_and_hms(2025, 9, 1, 2, 0, 0).unwrap(), true);
+        let retired = rss.clone();
+        retire_feed(&mut rss, Utc.with_ymd_and_hms(2025, 9, 8, 2, 0, 0).unwrap(), true);
+        assert_eq!(rss, retired);
+    }
+
+    #[test]
+    fn test_retired_at_unretired_feed() {
+        assert_eq!(retired_at(&black_wax()), None);
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// Namespace of the `atom:link` elements in the channel.
+pub const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+
+/// A link from the channel to a related resource, such as the feed's own URL
+/// (`rel="self"`) or a WebSub hub (`rel="hub"`).
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename = "atom:link")]
+pub struct AtomLink {
+    #[serde(rename = "@href")]
+    href: String,
+    #[serde(rename = "@rel")]
+    rel: String,
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
+    media_type: Option<String>,
+}
+
+impl AtomLink {
+    pub fn new<H: Into<String>, R: Into<String>>(href: H, rel: R) -> Self {
+        AtomLink { href: href.into(), rel: rel.into(), media_type: None }
+    }
+
+    /// The link to the feed itself.
+    pub fn self_link<H: Into<String>>(href: H, media_type: &str) -> Self {
+        AtomLink { media_type: Some(media_type.to_owned()), ..AtomLink::new(href, "self") }
+    }
+
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+
+    pub fn rel(&self) -> &str {
+        &self.rel
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AtomLink;
+    use quick_xml::se::to_string;
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(
+            to_string(&AtomLink::self_link("https://feeds/rss.xml", "application/rss+xml")).unwrap(),
+            "<atom:link href=\"https://feeds/rss.xml\" rel=\"self\" type=\"application/rss+xml\"/>"
+        );
+        assert_eq!(
+            to_string(&AtomLink::new("https://hub", "hub")).unwrap(),
+            "<atom:link href=\"https://hub\" rel=\"hub\"/>"
+        );
+    }
+}
@@ -1,10 +1,10 @@
 use derive_builder::Builder;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[serde_with::apply(
     Option => #[builder(default)] #[serde(skip_serializing_if = "Option::is_none")],
 )]
-#[derive(Serialize, Builder, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug)]
 #[serde(rename = "category")]
 #[builder(build_fn(private, name = "fallible_build"), setter(into))]
 pub struct Category {
@@ -25,6 +25,12 @@ impl CategoryBuilder {
     }
 }
 
+impl Category {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::CategoryBuilder;
@@ -1,14 +1,18 @@
+use crate::rss::atom_link::AtomLink;
 use crate::rss::category::Category;
 use crate::rss::image::Image;
 use crate::rss::item::Item;
+use crate::rss::itunes_category::ItunesCategory;
+use crate::rss::itunes_image::ItunesImage;
+use crate::rss::live_item::LiveItem;
 use derive_builder::Builder;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[serde_with::apply(
     Option => #[builder(default)] #[serde(skip_serializing_if = "Option::is_none")],
-    Vec => #[builder(default)],
+    Vec => #[builder(default)] #[serde(default)],
 )]
-#[derive(Serialize, Builder, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug)]
 #[builder(build_fn(private, name = "fallible_build"), setter(into))]
 #[serde(rename = "channel", rename_all = "camelCase")]
 pub struct Channel {
@@ -21,7 +25,7 @@ pub struct Channel {
     web_master: Option<String>,
     pub_date: Option<String>,
     last_build_date: Option<String>,
-    category: Option<Category>,
+    category: Vec<Category>,
     generator: Option<String>,
     docs: Option<String>,
     // cloud: Option<String>,
@@ -31,6 +35,22 @@ pub struct Channel {
     // textInput: Option<String>,
     skip_hours: Option<u8>,
     skip_days: Option<String>,
+    #[serde(rename(serialize = "itunes:image", deserialize = "itunes-image"))]
+    itunes_image: Option<ItunesImage>,
+    #[serde(rename(serialize = "itunes:category", deserialize = "itunes-category"))]
+    itunes_category: Option<ItunesCategory>,
+    #[serde(rename(serialize = "itunes:explicit", deserialize = "itunes-explicit"))]
+    itunes_explicit: Option<bool>,
+    #[serde(rename(serialize = "itunes:new-feed-url", deserialize = "itunes-new-feed-url"))]
+    itunes_new_feed_url: Option<String>,
+    #[serde(rename(serialize = "itunes:complete", deserialize = "itunes-complete"))]
+    itunes_complete: Option<String>,
+    #[serde(rename(serialize = "atom:link", deserialize = "atom-link"))]
+    atom_link: Vec<AtomLink>,
+    #[serde(rename(serialize = "podcast:locked", deserialize = "podcast-locked"))]
+    podcast_locked: Option<String>,
+    #[serde(rename(serialize = "podcast:liveItem", deserialize = "podcast-liveItem"))]
+    live_item: Option<LiveItem>,
     item: Vec<Item>,
 }
 
@@ -52,6 +72,130 @@ impl ChannelBuilder {
     }
 }
 
+impl Channel {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn link(&self) -> &str {
+        &self.link
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub fn pub_date(&self) -> Option<&str> {
+        self.pub_date.as_deref()
+    }
+
+    pub fn last_build_date(&self) -> Option<&str> {
+        self.last_build_date.as_deref()
+    }
+
+    /// Sets the time the channel's content last changed, as an RFC 822 date.
+    pub fn set_last_build_date(&mut self, date: String) {
+        self.last_build_date = Some(date);
+    }
+
+    /// The channel's first category, its free-text one for program feeds.
+    pub fn category(&self) -> Option<&Category> {
+        self.category.first()
+    }
+
+    pub fn categories(&self) -> &[Category] {
+        &self.category
+    }
+
+    pub fn add_category(&mut self, category: Category) {
+        self.category.push(category);
+    }
+
+    pub fn itunes_category(&self) -> Option<&ItunesCategory> {
+        self.itunes_category.as_ref()
+    }
+
+    pub fn set_itunes_category(&mut self, category: ItunesCategory) {
+        self.itunes_category = Some(category);
+    }
+
+    pub fn image(&self) -> Option<&Image> {
+        self.image.as_ref()
+    }
+
+    pub fn itunes_image(&self) -> Option<&ItunesImage> {
+        self.itunes_image.as_ref()
+    }
+
+    pub fn itunes_explicit(&self) -> Option<bool> {
+        self.itunes_explicit
+    }
+
+    pub fn itunes_new_feed_url(&self) -> Option<&str> {
+        self.itunes_new_feed_url.as_deref()
+    }
+
+    /// Tells podcast apps the feed's canonical URL, which subscribers are moved to.
+    pub fn set_itunes_new_feed_url(&mut self, url: String) {
+        self.itunes_new_feed_url = Some(url);
+    }
+
+    /// Whether `itunes:complete` marks the feed as never having episodes added again.
+    pub fn itunes_complete(&self) -> bool {
+        self.itunes_complete.as_deref().is_some_and(|complete| complete.eq_ignore_ascii_case("yes"))
+    }
+
+    pub fn set_itunes_complete(&mut self) {
+        self.itunes_complete = Some("Yes".to_owned());
+    }
+
+    /// Whether `podcast:locked` asks other platforms not to import the feed.
+    pub fn podcast_locked(&self) -> bool {
+        self.podcast_locked.as_deref().is_some_and(|locked| locked.eq_ignore_ascii_case("yes"))
+    }
+
+    pub fn set_podcast_locked(&mut self) {
+        self.podcast_locked = Some("yes".to_owned());
+    }
+
+    pub fn atom_links(&self) -> &[AtomLink] {
+        &self.atom_link
+    }
+
+    pub(crate) fn add_atom_link(&mut self, link: AtomLink) {
+        self.atom_link.push(link);
+    }
+
+    /// The URL of the `atom:link rel="self"`, the feed's own URL.
+    pub fn self_link(&self) -> Option<&str> {
+        self.atom_link.iter().find(|link| link.rel() == "self").map(AtomLink::href)
+    }
+
+    pub fn live_item(&self) -> Option<&LiveItem> {
+        self.live_item.as_ref()
+    }
+
+    pub fn set_live_item(&mut self, live_item: LiveItem) {
+        self.live_item = Some(live_item);
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.item
+    }
+
+    pub fn items_mut(&mut self) -> &mut [Item] {
+        &mut self.item
+    }
+
+    pub fn add_item(&mut self, item: Item) {
+        self.item.push(item);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ChannelBuilder;
@@ -156,7 +300,7 @@ mod tests {
                     <category domain=\"https://category.domain\">category</category>\
                     <comments>https://some.com/link-to-comments</comments>\
                     <enclosure url=\"https://enclosure/url.mp3\" length=\"1234\" type=\"audio/mpeg\"/>\
-                    <guid isPermalink=\"true\">https://inessential.com/123</guid>\
+                    <guid isPermaLink=\"true\">https://inessential.com/123</guid>\
                     <pubDate>2015-01-01T00:00:00Z</pubDate>\
                     <source url=\"https://inessential.com/123\">The Source</source>\
                 </item>\
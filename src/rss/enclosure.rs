@@ -1,6 +1,6 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename = "enclosure")]
 pub struct Enclosure {
     #[serde(rename = "@url")]
@@ -19,6 +19,18 @@ impl Enclosure {
             media_type: media_type.into()
         }
     }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn length(&self) -> Option<u64> {
+        self.length
+    }
+
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
 }
 
 #[cfg(test)]
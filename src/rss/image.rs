@@ -1,10 +1,10 @@
 use derive_builder::Builder;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[serde_with::apply(
     Option => #[builder(default)] #[serde(skip_serializing_if = "Option::is_none")],
 )]
-#[derive(Serialize, Builder, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug)]
 #[builder(build_fn(private, name = "fallible_build"), setter(into))]
 #[serde(rename = "image")]
 pub struct Image {
@@ -34,6 +34,20 @@ impl ImageBuilder {
     }
 }
 
+impl Image {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn width(&self) -> Option<u32> {
+        self.width
+    }
+
+    pub fn height(&self) -> Option<u32> {
+        self.height
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ImageBuilder;
@@ -1,15 +1,16 @@
 use derive_builder::Builder;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use crate::rss::category::Category;
 use crate::rss::enclosure::Enclosure;
 use crate::rss::item_guid::ItemGuid;
 use crate::rss::item_source::ItemSource;
+use crate::rss::podcast_person::PodcastPerson;
 
 #[serde_with::apply(
     Option => #[builder(default)] #[serde(skip_serializing_if = "Option::is_none")],
-    Vec => #[builder(default)],
+    Vec => #[builder(default)] #[serde(default)],
 )]
-#[derive(Serialize, Builder, Clone, Default, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Builder, Clone, Default, PartialEq, Debug)]
 #[builder(build_fn(private, name = "fallible_build"), setter(into), default)]
 #[serde(rename = "item", rename_all = "camelCase")]
 pub struct Item {
@@ -23,6 +24,11 @@ pub struct Item {
     guid: Option<ItemGuid>,
     pub_date: Option<String>,
     source: Option<ItemSource>,
+    /// Running time in seconds.
+    #[serde(rename(serialize = "itunes:duration", deserialize = "itunes-duration"))]
+    itunes_duration: Option<u32>,
+    #[serde(rename(serialize = "podcast:person", deserialize = "podcast-person"))]
+    podcast_person: Vec<PodcastPerson>,
 }
 
 impl ItemBuilder {
@@ -39,6 +45,48 @@ impl ItemBuilder {
     }
 }
 
+impl Item {
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn enclosure(&self) -> Option<&Enclosure> {
+        self.enclosure.as_ref()
+    }
+
+    pub fn guid(&self) -> Option<&ItemGuid> {
+        self.guid.as_ref()
+    }
+
+    pub fn set_guid(&mut self, guid: ItemGuid) {
+        self.guid = Some(guid);
+    }
+
+    pub fn pub_date(&self) -> Option<&str> {
+        self.pub_date.as_deref()
+    }
+
+    pub fn podcast_persons(&self) -> &[PodcastPerson] {
+        &self.podcast_person
+    }
+
+    pub fn itunes_duration(&self) -> Option<u32> {
+        self.itunes_duration
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Item,ItemBuilder};
@@ -127,7 +175,7 @@ mod tests {
                 <category domain=\"https://category.domain\">category</category>\
                 <comments>https://some.com/link-to-comments</comments>\
                 <enclosure url=\"https://enclosure/url.mp3\" length=\"1234\" type=\"audio/mpeg\"/>\
-                <guid isPermalink=\"true\">https://inessential.com/123</guid>\
+                <guid isPermaLink=\"true\">https://inessential.com/123</guid>\
                 <pubDate>2015-01-01T00:00:00Z</pubDate>\
                 <source url=\"https://inessential.com/123\">The Source</source>\
             </item>"
@@ -1,14 +1,14 @@
 use derive_builder::Builder;
-use serde::{Serialize};
+use serde::{Deserialize, Serialize};
 
 #[serde_with::apply(
     Option => #[builder(default)] #[serde(skip_serializing_if = "Option::is_none")],
 )]
-#[derive(Serialize, Builder, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug)]
 #[builder(build_fn(private, name = "fallible_build"), setter(into))]
 #[serde(rename = "guid")]
 pub struct ItemGuid {
-    #[serde(rename = "@isPermalink")]
+    #[serde(rename = "@isPermaLink", alias = "@isPermalink")]
     is_permalink: Option<bool>,
 
     #[serde(rename = "$text")]
@@ -25,9 +25,21 @@ impl ItemGuidBuilder {
     }
 }
 
+impl ItemGuid {
+    pub fn value(&self) -> &str {
+        &self.guid
+    }
+
+    /// Whether the guid is a URL to the item; RSS 2.0 assumes it is unless told otherwise.
+    pub fn is_permalink(&self) -> bool {
+        self.is_permalink.unwrap_or(true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::ItemGuidBuilder;
+    use super::{ItemGuid, ItemGuidBuilder};
+    use quick_xml::de::from_str;
     use quick_xml::se::to_string;
 
     #[test]
@@ -49,8 +61,15 @@ mod tests {
         let guid = ItemGuidBuilder::new("https://guid").is_permalink(true).build();
         assert_eq!(
             to_string(&guid).unwrap(),
-            "<guid isPermalink=\"true\">https://guid</guid>"
+            "<guid isPermaLink=\"true\">https://guid</guid>"
         );
     }
 
+    #[test]
+    fn test_deserialize_misspelled_permalink() {
+        // Feeds written before the attribute's spelling was fixed are still read back.
+        let guid: ItemGuid = from_str("<guid isPermalink=\"false\">https://guid</guid>").unwrap();
+        assert_eq!(guid, ItemGuidBuilder::new("https://guid").is_permalink(false).build());
+    }
+
 }
\ No newline at end of file
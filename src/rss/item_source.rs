@@ -1,10 +1,10 @@
 use derive_builder::Builder;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[serde_with::apply(
     Option => #[builder(default)] #[serde(skip_serializing_if = "Option::is_none")],
 )]
-#[derive(Serialize, Builder, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug)]
 #[builder(build_fn(private, name = "fallible_build"), setter(into))]
 #[serde(rename = "source")]
 pub struct ItemSource {
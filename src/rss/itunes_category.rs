@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// A podcast category from Apple's fixed list, optionally narrowed to one of
+/// its subcategories, e.g. Music > Music Commentary.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename = "itunes:category")]
+pub struct ItunesCategory {
+    #[serde(rename = "@text")]
+    text: String,
+    #[serde(
+        rename(serialize = "itunes:category", deserialize = "itunes-category"),
+        skip_serializing_if = "Option::is_none"
+    )]
+    subcategory: Option<Box<ItunesCategory>>,
+}
+
+impl ItunesCategory {
+    pub fn new<T: Into<String>>(text: T) -> Self {
+        ItunesCategory { text: text.into(), subcategory: None }
+    }
+
+    pub fn with_subcategory<T: Into<String>>(self, subcategory: T) -> Self {
+        ItunesCategory { subcategory: Some(Box::new(ItunesCategory::new(subcategory))), ..self }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn subcategory(&self) -> Option<&ItunesCategory> {
+        self.subcategory.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ItunesCategory;
+    use quick_xml::se::to_string;
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(to_string(&ItunesCategory::new("Music")).unwrap(), r#"<itunes:category text="Music"/>"#);
+        assert_eq!(
+            to_string(&ItunesCategory::new("Music").with_subcategory("Music Commentary")).unwrap(),
+            r#"<itunes:category text="Music"><itunes:category text="Music Commentary"/></itunes:category>"#
+        );
+    }
+}
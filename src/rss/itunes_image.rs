@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Podcast artwork, as read by Apple Podcasts and most other podcast clients.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename = "itunes:image")]
+pub struct ItunesImage {
+    #[serde(rename = "@href")]
+    href: String,
+}
+
+impl ItunesImage {
+    pub fn new<H: Into<String>>(href: H) -> Self {
+        ItunesImage { href: href.into() }
+    }
+
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ItunesImage;
+    use quick_xml::se::to_string;
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(
+            to_string(&ItunesImage::new("https://artwork.jpg")).unwrap(),
+            "<itunes:image href=\"https://artwork.jpg\"/>"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use crate::rss::enclosure::Enclosure;
+use crate::rss::item_guid::ItemGuid;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LiveItemStatus {
+    Pending,
+    Live,
+    Ended,
+}
+
+/// A show streaming live, from the Podcasting 2.0 namespace. Its enclosure
+/// points at the live stream rather than a recording.
+#[serde_with::apply(
+    Option => #[builder(default)] #[serde(skip_serializing_if = "Option::is_none")],
+)]
+#[derive(Serialize, Deserialize, Builder, Clone, PartialEq, Debug)]
+#[builder(build_fn(private, name = "fallible_build"), setter(into))]
+#[serde(rename = "podcast:liveItem")]
+pub struct LiveItem {
+    #[serde(rename = "@status")]
+    status: LiveItemStatus,
+    /// ISO 8601 start time.
+    #[serde(rename = "@start")]
+    start: String,
+    /// ISO 8601 end time.
+    #[serde(rename = "@end")]
+    end: String,
+    title: Option<String>,
+    link: Option<String>,
+    description: Option<String>,
+    author: Option<String>,
+    enclosure: Enclosure,
+    guid: Option<ItemGuid>,
+}
+
+impl LiveItemBuilder {
+    pub fn new<S: Into<String>, E: Into<String>>(
+        status: LiveItemStatus,
+        start: S,
+        end: E,
+        enclosure: Enclosure,
+    ) -> Self {
+        LiveItemBuilder::default()
+            .status(status)
+            .start(start)
+            .end(end)
+            .enclosure(enclosure)
+            .to_owned()
+    }
+
+    pub fn build(&mut self) -> LiveItem {
+        self.fallible_build().expect("All required fields set.")
+    }
+}
+
+impl LiveItem {
+    pub fn status(&self) -> LiveItemStatus {
+        self.status
+    }
+
+    pub fn enclosure(&self) -> &Enclosure {
+        &self.enclosure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LiveItemBuilder, LiveItemStatus};
+    use crate::rss::enclosure::Enclosure;
+    use quick_xml::se::to_string;
+
+    #[test]
+    fn test_serialize() {
+        let live_item = LiveItemBuilder::new(
+            LiveItemStatus::Live,
+            "2025-08-25T11:00:00+10:00",
+            "2025-08-25T13:00:00+10:00",
+            Enclosure::new("https://stream.aac", Some(0), "audio/aac"),
+        )
+        .title("Black Wax".to_owned())
+        .build();
+        assert_eq!(
+            to_string(&live_item).unwrap(),
+            "<podcast:liveItem status=\"live\" start=\"2025-08-25T11:00:00+10:00\" end=\"2025-08-25T13:00:00+10:00\">\
+                <title>Black Wax</title>\
+                <enclosure url=\"https://stream.aac\" length=\"0\" type=\"audio/aac\"/>\
+            </podcast:liveItem>"
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use serde::{Serialize, Serializer};
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use quick_xml::reader::Reader;
+use quick_xml::writer::Writer;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 mod item_guid;
 mod category;
@@ -7,6 +10,11 @@ mod item_source;
 mod item;
 mod image;
 mod channel;
+mod itunes_image;
+mod itunes_category;
+mod live_item;
+mod atom_link;
+mod podcast_person;
 
 pub use item_guid::*;
 pub use category::*;
@@ -15,12 +23,29 @@ pub use item_source::*;
 pub use item::*;
 pub use image::*;
 pub use channel::*;
+pub use itunes_image::*;
+pub use itunes_category::*;
+pub use live_item::*;
+pub use atom_link::*;
+pub use podcast_person::*;
 
-#[derive(Serialize, PartialEq, Debug)]
+/// Namespace of the `itunes:` elements read by podcast clients.
+pub const ITUNES_NAMESPACE: &str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+
+/// Namespace of the Podcasting 2.0 `podcast:` elements.
+pub const PODCAST_NAMESPACE: &str = "https://podcastindex.org/namespace/1.0";
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(rename = "rss")]
 pub struct Rss {
     #[serde(rename = "@version")]
     version: RssVersion,
+    #[serde(rename = "@xmlns:itunes", skip_serializing_if = "Option::is_none")]
+    xmlns_itunes: Option<String>,
+    #[serde(rename = "@xmlns:podcast", skip_serializing_if = "Option::is_none")]
+    xmlns_podcast: Option<String>,
+    #[serde(rename = "@xmlns:atom", skip_serializing_if = "Option::is_none")]
+    xmlns_atom: Option<String>,
     channel: Channel,
 }
 
@@ -28,12 +53,41 @@ impl Rss {
     pub fn new(channel: Channel) -> Self {
         Rss {
             version: RssVersion::RSS2_0,
+            xmlns_itunes: Some(ITUNES_NAMESPACE.to_owned()),
+            xmlns_podcast: Some(PODCAST_NAMESPACE.to_owned()),
+            xmlns_atom: None,
             channel
         }
     }
+
+    /// Adds an `atom:link` to the channel, declaring the Atom namespace.
+    pub fn add_atom_link(&mut self, link: AtomLink) {
+        self.xmlns_atom = Some(ATOM_NAMESPACE.to_owned());
+        self.channel.add_atom_link(link);
+    }
+
+    /// Declares the `itunes:` and `podcast:` namespaces, which a feed read
+    /// back from the legacy serialization lacks.
+    pub fn declare_podcast_namespaces(&mut self) {
+        self.xmlns_itunes = Some(ITUNES_NAMESPACE.to_owned());
+        self.xmlns_podcast = Some(PODCAST_NAMESPACE.to_owned());
+    }
+
+    /// Parses a feed previously written by this crate or any other RSS 2.0 feed.
+    pub fn from_xml(xml: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(quick_xml::de::from_str(&unprefix_element_names(xml)?)?)
+    }
+
+    pub fn channel(&self) -> &Channel {
+        &self.channel
+    }
+
+    pub fn channel_mut(&mut self) -> &mut Channel {
+        &mut self.channel
+    }
 }
 
-#[derive(PartialEq,Debug)]
+#[derive(Clone,PartialEq,Debug)]
 pub enum RssVersion {
     RSS2_0,
 }
@@ -55,6 +109,45 @@ impl Serialize for RssVersion {
     }
 }
 
+/// quick-xml's deserializer matches elements by local name, so `itunes:image`
+/// would be taken for `image`. Prefixed elements are renamed `prefix-local`
+/// before deserializing, which is what the namespaced fields expect.
+fn unprefix_element_names(xml: &str) -> Result<String, Box<dyn std::error::Error>> {
+    fn unprefixed(name: &[u8]) -> String {
+        String::from_utf8_lossy(name).replace(':', "-")
+    }
+
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new(Vec::new());
+    loop {
+        match reader.read_event()? {
+            Event::Eof => break,
+            Event::Start(start) => writer.write_event(Event::Start(
+                BytesStart::new(unprefixed(start.name().as_ref())).with_attributes(start.attributes().flatten()),
+            ))?,
+            Event::Empty(start) => writer.write_event(Event::Empty(
+                BytesStart::new(unprefixed(start.name().as_ref())).with_attributes(start.attributes().flatten()),
+            ))?,
+            Event::End(end) => writer.write_event(Event::End(BytesEnd::new(unprefixed(end.name().as_ref()))))?,
+            event => writer.write_event(event)?,
+        }
+    }
+    Ok(String::from_utf8(writer.into_inner())?)
+}
+
+impl<'de> Deserialize<'de> for RssVersion {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let version = String::deserialize(deserializer)?;
+        match version.as_str() {
+            "2.0" => Ok(Self::RSS2_0),
+            other => Err(serde::de::Error::custom(format!("unsupported RSS version {other}"))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -64,6 +157,9 @@ mod tests {
     fn test_serialize() {
         let data = Rss {
             version: RssVersion::RSS2_0,
+            xmlns_itunes: None,
+            xmlns_podcast: None,
+            xmlns_atom: None,
             channel: ChannelBuilder::new(
                     "some-title",
                     "https://www.google.com",
@@ -82,4 +178,19 @@ mod tests {
             </rss>"
         );
     }
+
+    #[test]
+    fn test_from_xml_round_trip() {
+        let mut rss = Rss::new(
+            ChannelBuilder::new("some-title", "https://www.google.com", "A description")
+                .image(ImageBuilder::new("https://image.png", "some-title", "https://www.google.com").build())
+                .itunes_image(ItunesImage::new("https://artwork.png"))
+                .itunes_explicit(false)
+                .item(vec![ItemBuilder::with_title("item").itunes_duration(60u32).build()])
+                .build(),
+        );
+        rss.add_atom_link(AtomLink::self_link("https://feed.xml", "application/rss+xml"));
+        let xml = quick_xml::se::to_string(&rss).unwrap();
+        assert_eq!(Rss::from_xml(&xml).unwrap(), rss);
+    }
 }
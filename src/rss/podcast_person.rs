@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// A person involved in an item, from the Podcasting 2.0 namespace. Without
+/// a role, apps take them to be its host.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename = "podcast:person")]
+pub struct PodcastPerson {
+    #[serde(rename = "@role", skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(rename = "@href", skip_serializing_if = "Option::is_none")]
+    href: Option<String>,
+    #[serde(rename = "$text")]
+    name: String,
+}
+
+impl PodcastPerson {
+    pub fn host<S: Into<String>>(name: S) -> Self {
+        PodcastPerson { role: Some("host".to_owned()), href: None, name: name.into() }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn role(&self) -> Option<&str> {
+        self.role.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PodcastPerson;
+    use quick_xml::se::to_string;
+
+    #[test]
+    fn test_serialize() {
+        assert_eq!(
+            to_string(&PodcastPerson::host("Adam Rudegeair")).unwrap(),
+            r#"<podcast:person role="host">Adam Rudegeair</podcast:person>"#
+        );
+    }
+}
@@ -1,8 +1,11 @@
 #[macro_export]
 macro_rules! macro_generate_rss_custom {
-    ($writer:expr, $options:expr) => {{
+    ($writer:expr, $options:expr) => {
+        macro_generate_rss_custom!($writer, $options, None::<&str>)
+    };
+    ($writer:expr, $options:expr, $stylesheet:expr) => {{
         use quick_xml::events::{
-            BytesDecl, BytesEnd, BytesStart, BytesText, Event,
+            BytesDecl, BytesEnd, BytesPI, BytesStart, BytesText, Event,
         };
 
         let mut writer = $writer;
@@ -12,6 +15,9 @@ macro_rules! macro_generate_rss_custom {
             Some("utf-8"),
             None,
         )))?;
+        if let Some(stylesheet) = $stylesheet {
+            writer.write_event(Event::PI(BytesPI::new($crate::xml_stylesheet_instruction(stylesheet))))?;
+        }
 
         let mut rss_start = BytesStart::new("rss");
         rss_start.push_attribute(("version", "2.0"));
@@ -82,10 +88,10 @@ macro_rules! macro_generate_rss_custom {
             macro_write_element!(writer, "author", item.author.as_str())?;
             macro_write_element!(writer, "guid", item.guid.as_str())?;
             macro_write_element!(writer, "pubDate", item.pub_date.as_str())?;
-            if item.enclosure.is_some() {
+            if let Some(enclosure) = item.enclosure {
                 writer.write_event(Event::Empty(BytesStart::from_content(
                     format!("enclosure url=\"{}\" type=\"audio/mp4\"",
-                        item.enclosure.unwrap()
+                        enclosure
                     ),
                     9
                 )))?;
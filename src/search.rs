@@ -0,0 +1,166 @@
+use crate::archive::{Archive, SearchMatch, SearchQuery};
+use crate::artist_feed::{format_offset, track_offset};
+use crate::{enclosure_url, episode_link, episode_title};
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(clap::Args, Debug)]
+pub struct SearchArgs {
+    /// SQLite archive written by `--archive`
+    #[arg(long)]
+    pub archive: PathBuf,
+
+    #[arg(long)]
+    pub artist: Option<String>,
+
+    #[arg(long)]
+    pub track: Option<String>,
+
+    #[arg(long)]
+    pub release: Option<String>,
+
+    /// Text to find in episode titles and descriptions
+    #[arg(long)]
+    pub text: Option<String>,
+
+    #[arg(short, long, value_delimiter = ',')]
+    pub programs: Vec<String>,
+
+    /// Earliest air date, e.g. 2025-06-01
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+
+    /// Latest air date (inclusive), e.g. 2025-06-30
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+impl SearchArgs {
+    pub fn query(&self) -> SearchQuery {
+        SearchQuery {
+            artist: self.artist.clone(),
+            track: self.track.clone(),
+            release: self.release.clone(),
+            text: self.text.clone(),
+            programs: self.programs.clone(),
+            from: self.from,
+            to: self.to,
+        }
+    }
+}
+
+/// A search match flattened for display, with the URLs needed to listen to it.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SearchResult {
+    pub program: String,
+    pub program_name: Option<String>,
+    pub aired: NaiveDateTime,
+    pub episode: String,
+    pub artist: Option<String>,
+    pub track: Option<String>,
+    pub release: Option<String>,
+    /// Approximate time into the episode the track was played, e.g. `1:05:30`.
+    pub time_in_episode: Option<String>,
+    pub link: String,
+    pub enclosure_url: String,
+}
+
+impl From<SearchMatch> for SearchResult {
+    fn from(search_match: SearchMatch) -> Self {
+        let episode = &search_match.episode;
+        let track = search_match.track.as_ref();
+        SearchResult {
+            program: search_match.program_slug.clone(),
+            program_name: search_match.program_name.clone(),
+            aired: episode.start,
+            episode: episode_title(episode),
+            artist: track.and_then(|track| track.artist.clone()),
+            track: track.and_then(|track| track.track.clone().or(track.title.clone())),
+            release: track.and_then(|track| track.release.clone()),
+            time_in_episode: track
+                .and_then(|track| track_offset(episode, track))
+                .map(format_offset),
+            link: episode_link(&search_match.program_slug, &episode.start),
+            enclosure_url: enclosure_url(&search_match.program_slug, &episode.start),
+        }
+    }
+}
+
+pub fn search(args: &SearchArgs) -> Result<Vec<SearchResult>, Box<dyn std::error::Error>> {
+    if !args.archive.exists() {
+        return Err(format!("archive {} does not exist", args.archive.display()).into());
+    }
+    let archive = Archive::open(&args.archive)?;
+    Ok(archive
+        .search(&args.query())?
+        .into_iter()
+        .map(SearchResult::from)
+        .collect())
+}
+
+pub fn run_search(args: SearchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let results = search(&args)?;
+    let stdout = std::io::stdout();
+    write_results(stdout.lock(), &results, args.format)
+}
+
+pub fn write_results<W: Write>(
+    mut writer: W,
+    results: &[SearchResult],
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, results)?;
+            writeln!(writer)?;
+        }
+        OutputFormat::Table => {
+            let header = ["Program", "Aired", "Episode", "Time", "Track", "Enclosure"].map(str::to_owned);
+            let rows: Vec<[String; 6]> = results
+                .iter()
+                .map(|result| {
+                    let track = match (&result.artist, &result.track) {
+                        (Some(artist), Some(track)) => format!("{artist} - {track}"),
+                        (artist, track) => artist.clone().or(track.clone()).unwrap_or_default(),
+                    };
+                    [
+                        result.program.clone(),
+                        result.aired.format("%Y-%m-%d %H:%M").to_string(),
+                        result.episode.clone(),
+                        result.time_in_episode.clone().unwrap_or_default(),
+                        track,
+                        result.enclosure_url.clone(),
+                    ]
+                })
+                .collect();
+
+            let mut widths = header.clone().map(|column| column.chars().count());
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+            for row in std::iter::once(&header).chain(&rows) {
+                let cells: Vec<String> = row
+                    .iter()
+                    .zip(widths)
+                    .map(|(cell, width)| format!("{cell:width$}"))
+                    .collect();
+                writeln!(writer, "{}", cells.join("  ").trim_end())?;
+            }
+        }
+    }
+    Ok(())
+}
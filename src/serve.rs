@@ -0,0 +1,507 @@
+use crate::api::{API_PATH, ApiRoute};
+use crate::feed_format::FeedFormat;
+use crate::guid::PublishedGuids;
+use crate::{convert_to_rss_v2, generate_rss_feed, live, manifest};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use reqwest::Url;
+use tiny_http::{Header, Request, Response, Server};
+
+/// The station segment of feed paths, as in the generated `pbsfm/<program>/rss.xml` layout.
+const STATION_PATH: &str = "pbsfm";
+
+const ALLOWED_METHODS: &str = "GET, HEAD, OPTIONS";
+
+/// Responses kept at most, so requests for many programs or hosts can't grow the cache without bound.
+const MAX_CACHED_RESPONSES: usize = 256;
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    #[arg(short, long, default_value = "https://airnet.org.au")]
+    pub airnet_url: String,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    pub address: String,
+
+    /// Seconds a generated feed is served before it is generated again
+    #[arg(long, default_value_t = 300)]
+    pub cache_ttl: u64,
+
+    /// Program slugs that may be requested; any program when empty
+    #[arg(short, long, value_delimiter = ',')]
+    pub programs: Vec<String>,
+
+    /// Origin allowed to fetch feeds from a browser, or `*` for any origin
+    #[arg(long = "cors-origin")]
+    pub cors_origins: Vec<String>,
+
+    /// Output directory of the generated feeds, whose published guids served feeds keep
+    #[arg(short, long, default_value = "docs/feeds/")]
+    pub output_dir: PathBuf,
+}
+
+pub fn run_serve(args: ServeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let server = Server::http(&args.address).map_err(|err| format!("could not listen on {}: {err}", args.address))?;
+    println!("Serving feeds on http://{}/feeds/{STATION_PATH}/<program>", args.address);
+    println!("Serving the JSON API on http://{}{API_PATH}, described at {API_PATH}/openapi.json", args.address);
+    FeedServer::new(args.airnet_url, Duration::from_secs(args.cache_ttl), args.programs)
+        .with_cors_origins(args.cors_origins)
+        .with_published_feeds(args.output_dir)
+        .serve(&server);
+    Ok(())
+}
+
+/// The parts of an HTTP request a feed response depends on.
+#[derive(Debug, Default)]
+pub struct FeedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl FeedRequest {
+    pub fn new(method: &str, url: &str) -> Self {
+        FeedRequest { method: method.to_owned(), url: url.to_owned(), headers: Vec::new() }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn path(&self) -> &str {
+        self.url.split(['?', '#']).next().unwrap_or_default()
+    }
+
+    /// The URL the client requested, without its query, as seen through any
+    /// proxy setting `X-Forwarded-Proto`; `None` without a `Host` header.
+    fn public_url(&self) -> Option<String> {
+        let host = self.header("Host")?;
+        let scheme = self.header("X-Forwarded-Proto").unwrap_or("http");
+        Some(format!("{scheme}://{host}{}", self.path()))
+    }
+
+    /// The value of a query parameter.
+    fn param(&self, name: &str) -> Option<String> {
+        let url = Url::parse(&format!("http://localhost{}", self.url)).ok()?;
+        url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned())
+    }
+}
+
+/// A response before it is turned into HTTP, so it can be checked without a socket.
+#[derive(Debug, PartialEq)]
+pub struct FeedResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+}
+
+impl FeedResponse {
+    fn text(status: u16, body: impl Into<String>) -> Self {
+        FeedResponse {
+            status,
+            headers: vec![("Content-Type", "text/plain; charset=utf-8".to_owned())],
+            body: body.into().into_bytes(),
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// What a cached response was generated for.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+enum CacheKey {
+    /// A program's feed in a format, linking to the URL it was requested at.
+    Feed(String, FeedFormat, Option<String>),
+    Api(String),
+}
+
+struct CachedFeed {
+    body: Vec<u8>,
+    etag: String,
+    generated_at: Instant,
+}
+
+/// A feed path: either `rss.xml`, which is always RSS, or the program's
+/// canonical URL, whose format is negotiated.
+#[derive(Debug, PartialEq)]
+struct FeedPath {
+    program: String,
+    negotiated: bool,
+}
+
+/// Generates program feeds, and answers the JSON API, from Airnet when they are
+/// requested, keeping each response for `ttl` so clients polling the same
+/// feed don't each hit Airnet.
+pub struct FeedServer {
+    airnet_url: String,
+    ttl: Duration,
+    programs: Vec<String>,
+    cors_origins: Vec<String>,
+    published_feeds: Option<PathBuf>,
+    cache: HashMap<CacheKey, CachedFeed>,
+}
+
+impl FeedServer {
+    pub fn new(airnet_url: String, ttl: Duration, programs: Vec<String>) -> Self {
+        FeedServer { airnet_url, ttl, programs, cors_origins: Vec::new(), published_feeds: None, cache: HashMap::new() }
+    }
+
+    /// Allows browsers on these origins, or any origin for `*`, to fetch feeds.
+    pub fn with_cors_origins(mut self, cors_origins: Vec<String>) -> Self {
+        self.cors_origins = cors_origins;
+        self
+    }
+
+    /// Keeps the guids of the items already published in the feeds generated
+    /// to this output directory, so served feeds have the same guids.
+    pub fn with_published_feeds(mut self, output_dir: PathBuf) -> Self {
+        self.published_feeds = Some(output_dir);
+        self
+    }
+
+    /// Answers requests until the server is unblocked.
+    pub fn serve(mut self, server: &Server) {
+        for request in server.incoming_requests() {
+            let feed_request = FeedRequest {
+                method: request.method().as_str().to_owned(),
+                url: request.url().to_owned(),
+                headers: request
+                    .headers()
+                    .iter()
+                    .map(|header| (header.field.to_string(), header.value.to_string()))
+                    .collect(),
+            };
+            let response = self.respond(&feed_request);
+            if let Err(err) = send(request, response) {
+                eprintln!("Could not send response: {err}");
+            }
+        }
+    }
+
+    pub fn respond(&mut self, request: &FeedRequest) -> FeedResponse {
+        let mut response = self.respond_to_feed_request(request);
+        self.add_cors_headers(request, &mut response);
+        response
+    }
+
+    fn respond_to_feed_request(&mut self, request: &FeedRequest) -> FeedResponse {
+        let method = request.method.as_str();
+        if !["GET", "HEAD", "OPTIONS"].contains(&method) {
+            let mut response = FeedResponse::text(405, "Method not allowed");
+            response.headers.push(("Allow", ALLOWED_METHODS.to_owned()));
+            return response;
+        }
+        if let Some(route) = ApiRoute::from_path(request.path()) {
+            return self.respond_to_api_request(request, route);
+        }
+        let Some(FeedPath { program, negotiated }) = self.requested_feed(request.path()) else {
+            return FeedResponse::text(404, "Not found");
+        };
+        if method == "OPTIONS" {
+            return preflight_response();
+        }
+
+        let format = if !negotiated {
+            FeedFormat::Rss
+        } else if let Some(param) = request.param("format") {
+            match FeedFormat::from_param(&param) {
+                Some(format) => format,
+                None => return FeedResponse::text(400, format!("Unknown feed format: {param}")),
+            }
+        } else {
+            match FeedFormat::negotiate(request.header("Accept")) {
+                Some(format) => format,
+                None => return FeedResponse::text(406, "Feeds are available as RSS, Atom or JSON Feed"),
+            }
+        };
+
+        let self_url = request.public_url();
+        let key = CacheKey::Feed(program.clone(), format, self_url.clone());
+        let published_feed =
+            self.published_feeds.as_ref().map(|dir| dir.join(STATION_PATH).join(&program).join("rss.xml"));
+        let generate = |airnet_url: &str| {
+            generate_feed(airnet_url, &program, format, published_feed.as_deref(), self_url.as_deref())
+        };
+        if let Err(err) = self.refresh(&key, generate) {
+            eprintln!("Could not generate feed for {program}: {err}");
+            return FeedResponse::text(502, format!("Could not generate feed for {program}"));
+        }
+        let mut response = self.cached_response(request, &key, format.content_type());
+        if negotiated {
+            append_vary(&mut response, "Accept");
+        }
+        response
+    }
+
+    fn respond_to_api_request(&mut self, request: &FeedRequest, route: ApiRoute) -> FeedResponse {
+        if route.program().is_some_and(|program| !self.is_allowed(program)) {
+            return FeedResponse::text(404, "Not found");
+        }
+        if request.method == "OPTIONS" {
+            return preflight_response();
+        }
+        let key = CacheKey::Api(request.path().trim_end_matches('/').to_owned());
+        let programs = self.programs.clone();
+        if let Err(err) = self.refresh(&key, |airnet_url| route.fetch(airnet_url, &programs, Utc::now())) {
+            eprintln!("Could not answer {}: {err}", request.path());
+            return FeedResponse::text(502, "Could not fetch from Airnet");
+        }
+        self.cached_response(request, &key, "application/json")
+    }
+
+    /// Generates the response for `key` again unless it was generated within
+    /// the TTL. When Airnet fails, an expired response is kept and served.
+    fn refresh(
+        &mut self,
+        key: &CacheKey,
+        generate: impl FnOnce(&str) -> Result<Vec<u8>, Box<dyn std::error::Error>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let fresh = self.cache.get(key).is_some_and(|cached| cached.generated_at.elapsed() < self.ttl);
+        if !fresh {
+            match generate(&self.airnet_url) {
+                Ok(body) => {
+                    let etag = etag(&body);
+                    if !self.cache.contains_key(key) {
+                        self.evict_oldest();
+                    }
+                    self.cache.insert(key.clone(), CachedFeed { body, etag, generated_at: Instant::now() });
+                }
+                Err(err) if self.cache.contains_key(key) => eprintln!("Serving an expired response: {err}"),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes room for another response once the cache is full, dropping the
+    /// one generated longest ago.
+    fn evict_oldest(&mut self) {
+        if self.cache.len() < MAX_CACHED_RESPONSES {
+            return;
+        }
+        let oldest = self.cache.iter().min_by_key(|(_, cached)| cached.generated_at).map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            self.cache.remove(&oldest);
+        }
+    }
+
+    fn cached_response(&self, request: &FeedRequest, key: &CacheKey, content_type: &str) -> FeedResponse {
+        let cached = &self.cache[key];
+        let mut headers = vec![
+            ("ETag", cached.etag.clone()),
+            ("Cache-Control", format!("max-age={}", self.ttl.as_secs())),
+        ];
+        if request.header("If-None-Match").is_some_and(|tags| etag_matches(tags, &cached.etag)) {
+            return FeedResponse { status: 304, headers, body: Vec::new() };
+        }
+        headers.insert(0, ("Content-Type", content_type.to_owned()));
+        let body = if request.method == "HEAD" { Vec::new() } else { cached.body.clone() };
+        FeedResponse { status: 200, headers, body }
+    }
+
+    /// Lets the requesting origin read the response, if it is allowed. Unless
+    /// any origin is, responses vary by origin whether or not one is sent.
+    fn add_cors_headers(&self, request: &FeedRequest, response: &mut FeedResponse) {
+        let any_origin = self.cors_origins.iter().any(|allowed| allowed == "*");
+        if !any_origin && !self.cors_origins.is_empty() {
+            append_vary(response, "Origin");
+        }
+        let Some(origin) = request.header("Origin") else {
+            return;
+        };
+        let allow_origin = if any_origin {
+            "*".to_owned()
+        } else if self.cors_origins.iter().any(|allowed| allowed == origin) {
+            origin.to_owned()
+        } else {
+            return;
+        };
+        response.headers.push(("Access-Control-Allow-Origin", allow_origin));
+        if request.method == "OPTIONS" {
+            response.headers.push(("Access-Control-Allow-Methods", ALLOWED_METHODS.to_owned()));
+            response.headers.push(("Access-Control-Allow-Headers", "Accept, If-None-Match".to_owned()));
+            response.headers.push(("Access-Control-Max-Age", "86400".to_owned()));
+        } else {
+            response.headers.push(("Access-Control-Expose-Headers", "ETag".to_owned()));
+        }
+    }
+
+    /// The feed at a `/feeds/pbsfm/<program>` or `/feeds/pbsfm/<program>/rss.xml`
+    /// path, if it may be served.
+    fn requested_feed(&self, path: &str) -> Option<FeedPath> {
+        let segments: Vec<&str> = path.trim_start_matches('/').trim_end_matches('/').split('/').collect();
+        let (program, negotiated) = match segments.as_slice() {
+            ["feeds", STATION_PATH, program] => (*program, true),
+            ["feeds", STATION_PATH, program, "rss.xml"] => (*program, false),
+            _ => return None,
+        };
+        let valid_slug = !program.is_empty()
+            && program.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        (valid_slug && self.is_allowed(program)).then(|| FeedPath { program: program.to_owned(), negotiated })
+    }
+
+    fn is_allowed(&self, program: &str) -> bool {
+        self.programs.is_empty() || self.programs.iter().any(|allowed| allowed == program)
+    }
+}
+
+/// Generates the program's feed, keeping the guids already published in
+/// its generated feed, if one is given, and linking it to `self_url`.
+fn generate_feed(
+    airnet_url: &str,
+    program: &str,
+    format: FeedFormat,
+    published_feed: Option<&Path>,
+    self_url: Option<&str>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut rss_feed = generate_rss_feed(airnet_url, program, |program, episodes| {
+        convert_to_rss_v2(program, live::aired_episodes(episodes, Utc::now()))
+    })?;
+    if let Some(published_feed) = published_feed {
+        PublishedGuids::read(published_feed)?.apply(&mut rss_feed);
+    }
+    format.render(&rss_feed, self_url, Utc::now())
+}
+
+fn preflight_response() -> FeedResponse {
+    FeedResponse { status: 204, headers: vec![("Allow", ALLOWED_METHODS.to_owned())], body: Vec::new() }
+}
+
+fn append_vary(response: &mut FeedResponse, header: &str) {
+    match response.headers.iter_mut().find(|(name, _)| *name == "Vary") {
+        Some((_, vary)) => *vary = format!("{vary}, {header}"),
+        None => response.headers.push(("Vary", header.to_owned())),
+    }
+}
+
+fn etag(body: &[u8]) -> String {
+    format!("\"{}\"", manifest::content_hash(body))
+}
+
+/// Whether an `If-None-Match` header value names the entity tag.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+fn send(request: Request, feed_response: FeedResponse) -> std::io::Result<()> {
+    let mut response = Response::from_data(feed_response.body).with_status_code(feed_response.status);
+    for (name, value) in feed_response.headers {
+        let header = Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("Header names and values are ASCII.");
+        response.add_header(header);
+    }
+    request.respond(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_server(programs: &[&str]) -> FeedServer {
+        FeedServer::new(
+            "http://localhost:1".to_owned(),
+            Duration::from_secs(60),
+            programs.iter().map(|program| program.to_string()).collect(),
+        )
+    }
+
+    fn feed_path(program: &str, negotiated: bool) -> Option<FeedPath> {
+        Some(FeedPath { program: program.to_owned(), negotiated })
+    }
+
+    #[test]
+    fn test_requested_feed() {
+        let server = feed_server(&[]);
+        assert_eq!(server.requested_feed("/feeds/pbsfm/black-wax/rss.xml"), feed_path("black-wax", false));
+        assert_eq!(server.requested_feed("/feeds/pbsfm/black-wax"), feed_path("black-wax", true));
+        assert_eq!(server.requested_feed("/feeds/pbsfm/black-wax/"), feed_path("black-wax", true));
+        assert_eq!(server.requested_feed("/feeds/3rrr/black-wax/rss.xml"), None);
+        assert_eq!(server.requested_feed("/feeds/pbsfm/../rss.xml"), None);
+        assert_eq!(server.requested_feed("/feeds/pbsfm/black-wax/atom.xml"), None);
+
+        let server = feed_server(&["soul-time"]);
+        assert_eq!(server.requested_feed("/feeds/pbsfm/black-wax/rss.xml"), None);
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"xyz\", W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(!etag_matches("\"xyz\"", "\"abc\""));
+    }
+
+    #[test]
+    fn test_method_not_allowed() {
+        let request = FeedRequest::new("POST", "/feeds/pbsfm/black-wax/rss.xml");
+        assert_eq!(feed_server(&[]).respond(&request).status, 405);
+    }
+
+    #[test]
+    fn test_unknown_or_unacceptable_format() {
+        let mut server = feed_server(&[]);
+        let request = FeedRequest::new("GET", "/feeds/pbsfm/black-wax?format=html");
+        assert_eq!(server.respond(&request).status, 400);
+        let request = FeedRequest::new("GET", "/feeds/pbsfm/black-wax").with_header("Accept", "text/html");
+        assert_eq!(server.respond(&request).status, 406);
+    }
+
+    #[test]
+    fn test_cors_preflight() {
+        let mut server = feed_server(&[]).with_cors_origins(vec!["https://dashboard.example".to_owned()]);
+        let request = FeedRequest::new("OPTIONS", "/feeds/pbsfm/black-wax")
+            .with_header("Origin", "https://dashboard.example");
+        let response = server.respond(&request);
+        assert_eq!(response.status, 204);
+        assert_eq!(response.header("Access-Control-Allow-Origin"), Some("https://dashboard.example"));
+        assert_eq!(response.header("Access-Control-Allow-Methods"), Some("GET, HEAD, OPTIONS"));
+        assert_eq!(response.header("Vary"), Some("Origin"));
+
+        let request = FeedRequest::new("OPTIONS", "/feeds/pbsfm/black-wax").with_header("Origin", "https://other.example");
+        let response = server.respond(&request);
+        assert_eq!(response.header("Access-Control-Allow-Origin"), None);
+        assert_eq!(response.header("Vary"), Some("Origin"));
+
+        let request = FeedRequest::new("OPTIONS", "/feeds/pbsfm/black-wax");
+        assert_eq!(server.respond(&request).header("Vary"), Some("Origin"));
+    }
+
+    #[test]
+    fn test_public_url() {
+        let request =
+            FeedRequest::new("GET", "/feeds/pbsfm/black-wax?format=json").with_header("Host", "feeds.example");
+        assert_eq!(request.public_url().as_deref(), Some("http://feeds.example/feeds/pbsfm/black-wax"));
+        let request = request.with_header("X-Forwarded-Proto", "https");
+        assert_eq!(request.public_url().as_deref(), Some("https://feeds.example/feeds/pbsfm/black-wax"));
+        assert_eq!(FeedRequest::new("GET", "/feeds/pbsfm/black-wax").public_url(), None);
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let mut server = feed_server(&[]);
+        for n in 0..=MAX_CACHED_RESPONSES {
+            let key = CacheKey::Api(format!("/api/programs/{n}"));
+            server.refresh(&key, |_| Ok(Vec::new())).unwrap();
+        }
+        assert_eq!(server.cache.len(), MAX_CACHED_RESPONSES);
+        assert!(server.cache.contains_key(&CacheKey::Api(format!("/api/programs/{MAX_CACHED_RESPONSES}"))));
+    }
+}
@@ -0,0 +1,95 @@
+<?xml version="1.0" encoding="utf-8"?>
+<xsl:stylesheet version="1.0"
+                xmlns:xsl="http://www.w3.org/1999/XSL/Transform"
+                xmlns:atom="http://www.w3.org/2005/Atom"
+                xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"
+                exclude-result-prefixes="atom itunes">
+  <xsl:output method="html" version="1.0" encoding="utf-8" indent="yes"/>
+
+  <xsl:template match="/">
+    <xsl:variable name="channel" select="/rss/channel"/>
+    <xsl:variable name="artwork">
+      <xsl:choose>
+        <xsl:when test="$channel/itunes:image/@href"><xsl:value-of select="$channel/itunes:image/@href"/></xsl:when>
+        <xsl:otherwise><xsl:value-of select="$channel/image/url"/></xsl:otherwise>
+      </xsl:choose>
+    </xsl:variable>
+    <html lang="en">
+      <head>
+        <meta charset="utf-8"/>
+        <meta name="viewport" content="width=device-width,maximum-scale=2"/>
+        <title><xsl:value-of select="$channel/title"/> - RSS feed</title>
+        <style>
+          body { margin: 0; background: #f2f2f2; color: #222; line-height: 1.5;
+                 font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; }
+          a { color: #1e8cd8; }
+          header { background: linear-gradient(to top, #373737, #212121); color: #fff; }
+          .inner { max-width: 800px; margin: 0 auto; padding: 20px 10px; }
+          .channel { display: flex; align-items: center; }
+          .channel img { width: 128px; height: 128px; object-fit: cover; margin-right: 20px; }
+          .channel h1 { margin: 0; }
+          .subscribe { background: #fff; border: 1px solid #ccc; padding: 10px 15px; margin: 20px 0; }
+          .subscribe input { width: 70%; font-family: monospace; padding: 4px; }
+          .episode { border-top: 1px solid #ccc; padding: 10px 0; }
+          .episode h3 { margin: 0; }
+          .aired { margin: 0; color: #666; }
+          .episode audio { width: 100%; }
+        </style>
+      </head>
+      <body>
+        <header>
+          <div class="inner channel">
+            <xsl:if test="string($artwork)">
+              <img src="{$artwork}" alt="{$channel/title} artwork"/>
+            </xsl:if>
+            <div>
+              <h1><xsl:value-of select="$channel/title"/></h1>
+              <xsl:if test="string($channel/category)">
+                <div><xsl:value-of select="$channel/category"/></div>
+              </xsl:if>
+              <a href="{$channel/link}" style="color: #fff"><xsl:value-of select="$channel/link"/></a>
+            </div>
+          </div>
+        </header>
+        <main class="inner">
+          <section class="subscribe">
+            <p>
+              <strong>This is an RSS feed.</strong>
+              Subscribe by copying its URL into your podcast app, e.g. under "Follow a show by URL" or "Add podcast by RSS feed".
+            </p>
+            <p>
+              <input id="feed-url" type="text" readonly="readonly" value="{$channel/atom:link[@rel='self']/@href}"/>
+              <xsl:text> </xsl:text>
+              <button type="button" onclick="navigator.clipboard.writeText(document.getElementById('feed-url').value)">Copy feed URL</button>
+            </p>
+            <script>
+              var feedUrl = document.getElementById('feed-url');
+              if (!feedUrl.value) { feedUrl.value = window.location.href; }
+            </script>
+          </section>
+          <xsl:if test="$channel/itunes:complete = 'Yes'">
+            <p class="ended"><strong>This program has ended.</strong> No new episodes will be added to this feed.</p>
+          </xsl:if>
+          <p><xsl:value-of select="$channel/description"/></p>
+          <xsl:for-each select="$channel/item">
+            <article class="episode">
+              <h3>
+                <xsl:choose>
+                  <xsl:when test="link"><a href="{link}"><xsl:value-of select="title"/></a></xsl:when>
+                  <xsl:otherwise><xsl:value-of select="title"/></xsl:otherwise>
+                </xsl:choose>
+              </h3>
+              <p class="aired"><xsl:value-of select="pubDate"/></p>
+              <xsl:if test="string(description)">
+                <p><xsl:value-of select="description"/></p>
+              </xsl:if>
+              <xsl:if test="enclosure/@url">
+                <audio controls="controls" preload="none" src="{enclosure/@url}"></audio>
+              </xsl:if>
+            </article>
+          </xsl:for-each>
+        </main>
+      </body>
+    </html>
+  </xsl:template>
+</xsl:stylesheet>
@@ -0,0 +1,371 @@
+use crate::airnet::PlaylistCache;
+use crate::airnet::types::{Episode, PlaylistEntry, ProgramDetails};
+use crate::{enclosure_url, episode_link, episode_title, rm_query_params, station_datetime};
+use chrono::{DateTime, SecondsFormat};
+use minijinja::{AutoEscape, Environment, ErrorKind, Output, State, Value, context};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Directory of the site, relative to its root, holding a page for each program.
+pub const PROGRAM_PAGES_DIR: &str = "pbsfm";
+
+pub(crate) const SITE_TITLE: &str = "PBS radio RSS feeds";
+const OWNER_NAME: &str = "github.com/aringeri";
+const OWNER_URL: &str = "https://github.com/aringeri";
+
+/// File, in the output directory, of the stylesheet browsers render the feeds with.
+pub const FEED_STYLESHEET_FILE: &str = "feed.xsl";
+
+const STYLESHEET: &str = include_str!("assets/main.css");
+const FEED_STYLESHEET: &str = include_str!("assets/feed.xsl");
+const RSS_ICON: &[u8] = include_bytes!("assets/rss.png");
+
+/// Where the site links to each program's feed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeedLinks {
+    /// The public URL the feeds directory is served from.
+    Public(String),
+    /// The feeds directory, relative to the site's root.
+    Relative(String),
+}
+
+impl FeedLinks {
+    fn href(&self, root: &str, slug: &str) -> String {
+        match self {
+            FeedLinks::Public(base_url) => format!("{}/pbsfm/{slug}/rss.xml", base_url.trim_end_matches('/')),
+            FeedLinks::Relative(feeds_dir) => format!("{root}{feeds_dir}pbsfm/{slug}/rss.xml"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ProgramPage {
+    slug: String,
+    name: String,
+    broadcasters: String,
+    description: String,
+    category: Option<String>,
+    artwork_url: String,
+    link: String,
+    page_url: String,
+    feed_url: String,
+    /// When the program's feed was retired, if it has ended.
+    ended: Option<Ended>,
+    episodes: Vec<EpisodeEntry>,
+}
+
+#[derive(Serialize, Debug)]
+struct Ended {
+    at: String,
+    date: String,
+}
+
+#[derive(Serialize, Debug)]
+struct EpisodeEntry {
+    title: String,
+    description: Option<String>,
+    start: String,
+    aired: String,
+    duration: String,
+    link: String,
+    audio_url: String,
+    image_url: Option<String>,
+    tracks: Vec<Track>,
+}
+
+#[derive(Serialize, Debug)]
+struct Track {
+    time: Option<String>,
+    artist: Option<String>,
+    title: Option<String>,
+    release: Option<String>,
+}
+
+/// Renders the site's pages from templates compiled into the binary.
+pub struct Site {
+    env: Environment<'static>,
+    feed_links: FeedLinks,
+}
+
+impl Site {
+    pub fn new(feed_links: FeedLinks) -> Self {
+        let mut env = Environment::new();
+        env.set_formatter(escape_formatter);
+        env.add_template("layout.html", include_str!("templates/layout.html"))
+            .expect("The layout template is valid.");
+        env.add_template("index.html", include_str!("templates/index.html"))
+            .expect("The index template is valid.");
+        env.add_template("program.html", include_str!("templates/program.html"))
+            .expect("The program template is valid.");
+        Site { env, feed_links }
+    }
+
+    /// Writes the index page, a page for each program under `pbsfm/<program>/`
+    /// and the assets they share to `site_dir`. `retired` has the RFC 3339
+    /// retirement time of the programs that have ended, by slug.
+    pub fn write(
+        &self,
+        site_dir: &Path,
+        programs: &[(ProgramDetails, Vec<Episode>)],
+        playlists: &PlaylistCache,
+        retired: &BTreeMap<String, String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut sorted: Vec<&(ProgramDetails, Vec<Episode>)> = programs.iter().collect();
+        sorted.sort_by(|(a, _), (b, _)| a.slug.cmp(&b.slug));
+
+        std::fs::create_dir_all(site_dir)?;
+        std::fs::write(site_dir.join("index.html"), self.render_index(&sorted, retired)?)?;
+        self.write_program_pages(site_dir, programs, playlists, retired)?;
+
+        let css_dir = site_dir.join("assets/css");
+        std::fs::create_dir_all(&css_dir)?;
+        std::fs::write(css_dir.join("main.css"), STYLESHEET)?;
+        let images_dir = site_dir.join("assets/images");
+        std::fs::create_dir_all(&images_dir)?;
+        std::fs::write(images_dir.join("rss.png"), RSS_ICON)?;
+        Ok(())
+    }
+
+    /// Writes only the programs' pages, as `write` does, leaving the index
+    /// and assets as they are.
+    pub fn write_program_pages(
+        &self,
+        site_dir: &Path,
+        programs: &[(ProgramDetails, Vec<Episode>)],
+        playlists: &PlaylistCache,
+        retired: &BTreeMap<String, String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for (program, episodes) in programs {
+            let program_dir = site_dir.join(PROGRAM_PAGES_DIR).join(&program.slug);
+            std::fs::create_dir_all(&program_dir)?;
+            let ended = retired.get(&program.slug).map(String::as_str);
+            std::fs::write(program_dir.join("index.html"), self.render_program(program, episodes, playlists, ended)?)?;
+        }
+        Ok(())
+    }
+
+    fn render_index(
+        &self,
+        programs: &[&(ProgramDetails, Vec<Episode>)],
+        retired: &BTreeMap<String, String>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let root = "";
+        let programs = programs
+            .iter()
+            .map(|(program, _)| {
+                self.program_page(root, program, Vec::new(), retired.get(&program.slug).map(String::as_str))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.render("index.html", root, context! { programs })
+    }
+
+    fn render_program(
+        &self,
+        program: &ProgramDetails,
+        episodes: &[Episode],
+        playlists: &PlaylistCache,
+        ended: Option<&str>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let root = "../../";
+        let mut episodes: Vec<&Episode> = episodes.iter().collect();
+        episodes.sort_by_key(|episode| std::cmp::Reverse(episode.start));
+        let entries = episodes
+            .into_iter()
+            .map(|episode| episode_entry(program, episode, playlists.get(&program.slug, &episode.start)))
+            .collect();
+        let program = self.program_page(root, program, entries, ended)?;
+        self.render("program.html", root, context! { program })
+    }
+
+    fn render(&self, template: &str, root: &str, page: minijinja::Value) -> Result<String, Box<dyn std::error::Error>> {
+        let ctx = context! {
+            site_title => SITE_TITLE,
+            owner_name => OWNER_NAME,
+            owner_url => OWNER_URL,
+            root,
+            ..page
+        };
+        Ok(self.env.get_template(template)?.render(ctx)?)
+    }
+
+    /// The program as shown on a page `root` away from the site's root.
+    fn program_page(
+        &self,
+        root: &str,
+        program: &ProgramDetails,
+        episodes: Vec<EpisodeEntry>,
+        ended: Option<&str>,
+    ) -> Result<ProgramPage, Box<dyn std::error::Error>> {
+        let ended = ended
+            .map(|at| -> Result<Ended, chrono::ParseError> {
+                let date = DateTime::parse_from_rfc3339(at)?.format("%-d %B %Y").to_string();
+                Ok(Ended { at: at.to_owned(), date })
+            })
+            .transpose()?;
+        Ok(ProgramPage {
+            slug: program.slug.clone(),
+            name: program.name.clone(),
+            broadcasters: program.broadcasters.clone(),
+            description: program.description.clone(),
+            category: program.grid_description.clone().filter(|category| !category.is_empty()),
+            artwork_url: rm_query_params(&program.profile_image_url)?,
+            link: format!("https://www.pbsfm.org.au/program/{}", program.slug),
+            page_url: format!("{root}{PROGRAM_PAGES_DIR}/{}/", program.slug),
+            feed_url: self.feed_links.href(root, &program.slug),
+            ended,
+            episodes,
+        })
+    }
+}
+
+/// Writes the XSL stylesheet the feeds link to with `feed_stylesheet_href`.
+pub fn write_feed_stylesheet(output_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(output_dir)?;
+    std::fs::write(output_dir.join(FEED_STYLESHEET_FILE), FEED_STYLESHEET)?;
+    Ok(())
+}
+
+/// The href of the feed stylesheet from `feed_path`, a path within the
+/// output directory. Browsers only apply stylesheets from the feed's own
+/// origin, so it's always relative.
+pub fn feed_stylesheet_href(feed_path: &Path) -> String {
+    let depth = feed_path.components().count().saturating_sub(1);
+    format!("{}{FEED_STYLESHEET_FILE}", "../".repeat(depth))
+}
+
+/// Escapes strings for HTML as minijinja does, except for `/`, which only
+/// needs escaping in unquoted attributes and would clutter every URL.
+fn escape_formatter(out: &mut Output<'_>, state: &State<'_, '_>, value: &Value) -> Result<(), minijinja::Error> {
+    match value.as_str() {
+        Some(text) if !value.is_safe() && matches!(state.auto_escape(), AutoEscape::Html) => {
+            let mut escaped = String::with_capacity(text.len());
+            for c in text.chars() {
+                match c {
+                    '&' => escaped.push_str("&amp;"),
+                    '<' => escaped.push_str("&lt;"),
+                    '>' => escaped.push_str("&gt;"),
+                    '"' => escaped.push_str("&quot;"),
+                    '\'' => escaped.push_str("&#x27;"),
+                    c => escaped.push(c),
+                }
+            }
+            out.write_str(&escaped).map_err(|err| minijinja::Error::new(ErrorKind::WriteFailure, err.to_string()))
+        }
+        _ => minijinja::escape_formatter(out, state, value),
+    }
+}
+
+fn episode_entry(program: &ProgramDetails, episode: &Episode, playlist: Option<&[PlaylistEntry]>) -> EpisodeEntry {
+    let start = station_datetime(&episode.start);
+    EpisodeEntry {
+        title: episode_title(episode),
+        description: episode.description.clone().filter(|description| !description.is_empty()),
+        start: start.to_rfc3339_opts(SecondsFormat::Secs, false),
+        aired: start.format("%A %-d %B %Y, %-I:%M%P").to_string(),
+        duration: format_duration(episode.duration),
+        link: episode_link(&program.slug, &episode.start),
+        audio_url: enclosure_url(&program.slug, &episode.start),
+        image_url: episode.image_url.as_deref().and_then(|url| rm_query_params(url).ok()),
+        tracks: playlist
+            .unwrap_or_default()
+            .iter()
+            .filter(|entry| entry.is_track())
+            .map(|entry| Track {
+                time: entry.approximate_time.map(|time| time.format("%H:%M").to_string()),
+                artist: entry.artist.clone(),
+                title: entry.track.clone().or_else(|| entry.title.clone()),
+                release: entry.release.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// A duration in seconds as hours and minutes, e.g. "1h 30m".
+fn format_duration(seconds: u32) -> String {
+    let (hours, minutes) = (seconds / 3600, seconds % 3600 / 60);
+    match (hours, minutes) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn program() -> ProgramDetails {
+        ProgramDetails {
+            name: "Rhythm & Soul".to_owned(),
+            broadcasters: "Vince Peach".to_owned(),
+            description: "Soul <and> R&B".to_owned(),
+            grid_description: Some("Soul".to_owned()),
+            profile_image_url: "https://airnet/images/soul-time.jpg?w=64".to_owned(),
+            ..test_support::program("soul-time")
+        }
+    }
+
+    fn episode(day: u32) -> Episode {
+        let start = test_support::datetime(8, day, 15);
+        Episode { end: start + chrono::TimeDelta::minutes(90), duration: 5400, ..test_support::episode(start) }
+    }
+
+    #[test]
+    fn test_render_index() {
+        let site = Site::new(FeedLinks::Relative("feeds/".to_owned()));
+        let programs = [(program(), vec![episode(23)])];
+        let retired = BTreeMap::from([("soul-time".to_owned(), "2025-09-01T12:00:00+10:00".to_owned())]);
+        let index = site.render_index(&programs.iter().collect::<Vec<_>>(), &retired).unwrap();
+        assert!(index.contains(r#"<a href="pbsfm/soul-time/">"#));
+        assert!(index.contains(r#"<a href="feeds/pbsfm/soul-time/rss.xml">"#));
+        assert!(index.contains(r#"<img src="https://airnet/images/soul-time.jpg" alt="Rhythm &amp; Soul profile image""#));
+        assert!(index.contains("Presented by: Vince Peach"));
+        assert!(index.contains(r#"Ended <time datetime="2025-09-01T12:00:00+10:00">1 September 2025</time>"#));
+    }
+
+    #[test]
+    fn test_render_program() {
+        let site = Site::new(FeedLinks::Public("https://example.com/feeds/".to_owned()));
+        let mut playlists = PlaylistCache::default();
+        let track = PlaylistEntry {
+            entry_type: "track".to_owned(),
+            id: 1,
+            artist: Some("Aretha Franklin".to_owned()),
+            title: Some("Respect".to_owned()),
+            track: None,
+            release: Some("I Never Loved a Man".to_owned()),
+            time: None,
+            notes: None,
+            content_descriptors: None,
+            approximate_time: Some(test_support::datetime(8, 30, 15) + chrono::TimeDelta::minutes(5)),
+        };
+        let episodes = vec![episode(23), episode(30)];
+        playlists.insert("soul-time", &episodes[1].start, vec![track]);
+
+        let page = site.render_program(&program(), &episodes, &playlists, None).unwrap();
+        assert!(page.contains(r#"href="https://example.com/feeds/pbsfm/soul-time/rss.xml""#));
+        assert!(page.contains(r#"<link rel="stylesheet" href="../../assets/css/main.css">"#));
+        assert!(page.contains("<p>Soul &lt;and&gt; R&amp;B</p>"));
+        assert!(page.contains(
+            r#"<time datetime="2025-08-30T15:00:00+10:00">Saturday 30 August 2025, 3:00pm</time> (1h 30m)"#
+        ));
+        assert!(page.contains("<strong>Aretha Franklin</strong> - Respect <em>(I Never Loved a Man)</em>"));
+        assert!(page.find("Untitled - 2025-08-30").unwrap() < page.find("Untitled - 2025-08-23").unwrap());
+        assert!(!page.contains(r#"class="ended""#));
+    }
+
+    #[test]
+    fn test_feed_stylesheet_href() {
+        assert_eq!(feed_stylesheet_href(Path::new("pbsfm/black-wax/rss.xml")), "../../feed.xsl");
+        assert_eq!(feed_stylesheet_href(Path::new("pbsfm/smart/interviews/rss.xml")), "../../../feed.xsl");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(7200), "2h");
+        assert_eq!(format_duration(5400), "1h 30m");
+        assert_eq!(format_duration(1800), "30m");
+    }
+}
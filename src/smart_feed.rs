@@ -0,0 +1,64 @@
+use crate::aggregate::{aggregate_feed, program_episodes};
+use crate::airnet::PlaylistCache;
+use crate::airnet::types::{Episode, ProgramDetails};
+use crate::filter::Filter;
+use crate::rss::{ChannelBuilder, Rss};
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+
+/// Output directory of the smart feeds, e.g. `pbsfm/smart/interviews/rss.xml`.
+pub const SMART_FEEDS_DIR: &str = "smart";
+
+/// A themed feed of the episodes, from a set of programs, that match a filter.
+///
+/// ```toml
+/// [[smart_feeds]]
+/// name = "Interviews"
+/// slug = "interviews"
+/// programs = ["black-wax", "soul-time"]
+/// filter = "title ~ /interview/i or description ~ /interview/i"
+/// ```
+#[serde_as]
+#[derive(Deserialize, Debug, Clone)]
+pub struct SmartFeed {
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    /// Program slugs to draw episodes from; every processed program when empty.
+    #[serde(default)]
+    pub programs: Vec<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub filter: Filter,
+}
+
+impl SmartFeed {
+    pub fn includes_program(&self, slug: &str) -> bool {
+        self.programs.is_empty() || self.programs.iter().any(|program| program == slug)
+    }
+
+    /// Builds the feed from already fetched programs. When the filter needs
+    /// playlists they must be in `playlists`; missing ones count as empty.
+    pub fn generate(&self, programs: &[(ProgramDetails, Vec<Episode>)], playlists: &PlaylistCache) -> Rss {
+        let episodes = program_episodes(programs)
+            .into_iter()
+            .filter(|(program, _)| self.includes_program(&program.slug))
+            .filter(|(program, episode)| {
+                self.filter
+                    .matches(episode, playlists.get(&program.slug, &episode.start))
+            })
+            .collect();
+
+        let description = self
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("PBS 106.7FM episodes matching: {}", self.name));
+        aggregate_feed(
+            ChannelBuilder::new(
+                format!("PBS 106.7FM - {}", self.name),
+                "https://www.pbsfm.org.au",
+                description,
+            ),
+            episodes,
+        )
+    }
+}
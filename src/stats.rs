@@ -0,0 +1,338 @@
+use crate::airnet::{AirnetClient, PlaylistCache};
+use crate::airnet::types::{ContentDescriptors, Episode, PlaylistEntry, ProgramDetails};
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Number of tracks flagged with each content descriptor.
+///
+/// Descriptors that Airnet reports as `null` are counted as not flagged, so
+/// percentages are always relative to the total number of tracks played.
+#[derive(Serialize, Debug, PartialEq, Clone, Copy, Default)]
+pub struct DescriptorCounts {
+    pub tracks: u32,
+    pub australian: u32,
+    pub local: u32,
+    pub female: u32,
+    pub indigenous: u32,
+    pub new: u32,
+    pub gender_non_conforming: u32,
+}
+
+impl DescriptorCounts {
+    pub fn from_playlist(playlist: &[PlaylistEntry]) -> Self {
+        let mut counts = DescriptorCounts::default();
+        for entry in playlist.iter().filter(|entry| entry.is_track()) {
+            counts.tracks += 1;
+            if let Some(descriptors) = &entry.content_descriptors {
+                counts.add_descriptors(descriptors);
+            }
+        }
+        counts
+    }
+
+    fn add_descriptors(&mut self, descriptors: &ContentDescriptors) {
+        let flag = |value: Option<bool>| u32::from(value.unwrap_or(false));
+        self.australian += flag(descriptors.is_australian);
+        self.local += flag(descriptors.is_local);
+        self.female += flag(descriptors.is_female);
+        self.indigenous += flag(descriptors.is_indigenous);
+        self.new += flag(descriptors.is_new);
+        self.gender_non_conforming += flag(descriptors.is_gender_non_conforming);
+    }
+
+    pub fn add(&mut self, other: &DescriptorCounts) {
+        self.tracks += other.tracks;
+        self.australian += other.australian;
+        self.local += other.local;
+        self.female += other.female;
+        self.indigenous += other.indigenous;
+        self.new += other.new;
+        self.gender_non_conforming += other.gender_non_conforming;
+    }
+
+    /// Returns `None` when no tracks were played, as there is nothing to report.
+    pub fn percentages(&self) -> Option<ContentPercentages> {
+        if self.tracks == 0 {
+            return None;
+        }
+        let pct = |count: u32| (count as f64 * 1000.0 / self.tracks as f64).round() / 10.0;
+        Some(ContentPercentages {
+            australian: pct(self.australian),
+            local: pct(self.local),
+            female: pct(self.female),
+            indigenous: pct(self.indigenous),
+            new: pct(self.new),
+            gender_non_conforming: pct(self.gender_non_conforming),
+        })
+    }
+}
+
+/// Percentage of tracks (0-100, one decimal place) flagged with each descriptor.
+#[derive(Serialize, Debug, PartialEq, Clone, Copy)]
+pub struct ContentPercentages {
+    pub australian: f64,
+    pub local: f64,
+    pub female: f64,
+    pub indigenous: f64,
+    pub new: f64,
+    pub gender_non_conforming: f64,
+}
+
+impl ContentPercentages {
+    /// A single line summary suitable for appending to an item description.
+    pub fn description_line(&self) -> String {
+        format!(
+            "Content: {:.0}% Australian, {:.0}% local, {:.0}% female, {:.0}% Indigenous, {:.0}% new, {:.0}% gender non-conforming",
+            self.australian,
+            self.local,
+            self.female,
+            self.indigenous,
+            self.new,
+            self.gender_non_conforming,
+        )
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct EpisodeStats {
+    pub start: NaiveDateTime,
+    pub title: Option<String>,
+    pub counts: DescriptorCounts,
+    pub percentages: Option<ContentPercentages>,
+}
+
+impl EpisodeStats {
+    pub fn new(episode: &Episode, playlist: &[PlaylistEntry]) -> Self {
+        let counts = DescriptorCounts::from_playlist(playlist);
+        EpisodeStats {
+            start: episode.start,
+            title: episode.title.clone(),
+            counts,
+            percentages: counts.percentages(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct ProgramStats {
+    pub slug: String,
+    pub name: String,
+    pub counts: DescriptorCounts,
+    pub percentages: Option<ContentPercentages>,
+    pub episodes: Vec<EpisodeStats>,
+}
+
+impl ProgramStats {
+    pub fn new(program: &ProgramDetails, episodes: Vec<EpisodeStats>) -> Self {
+        let mut counts = DescriptorCounts::default();
+        for episode in &episodes {
+            counts.add(&episode.counts);
+        }
+        ProgramStats {
+            slug: program.slug.clone(),
+            name: program.name.clone(),
+            counts,
+            percentages: counts.percentages(),
+            episodes,
+        }
+    }
+
+    pub fn fetch(
+        client: &AirnetClient,
+        playlists: &mut PlaylistCache,
+        station: &str,
+        program: &ProgramDetails,
+        episodes: &[Episode],
+    ) -> Result<Self, reqwest::Error> {
+        let mut episode_stats = Vec::with_capacity(episodes.len());
+        for episode in episodes {
+            let playlist = playlists.fetch(client, station, &program.slug, &episode.start)?;
+            episode_stats.push(EpisodeStats::new(episode, playlist));
+        }
+        Ok(ProgramStats::new(program, episode_stats))
+    }
+
+    /// Appends the content statistics line to the description of each episode with a playlist.
+    pub fn annotate_descriptions(&self, episodes: Vec<Episode>) -> Vec<Episode> {
+        episodes
+            .into_iter()
+            .map(|mut episode| {
+                let line = self
+                    .episodes
+                    .iter()
+                    .find(|stats| stats.start == episode.start)
+                    .and_then(|stats| stats.percentages)
+                    .map(|percentages| percentages.description_line());
+                if let Some(line) = line {
+                    episode.description = Some(match episode.description {
+                        Some(description) if !description.is_empty() => {
+                            format!("{description}\n\n{line}")
+                        }
+                        _ => line,
+                    });
+                }
+                episode
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    program: &'a str,
+    slug: &'a str,
+    episode_start: Option<NaiveDateTime>,
+    episode_title: Option<&'a str>,
+    tracks: u32,
+    australian_pct: Option<f64>,
+    local_pct: Option<f64>,
+    female_pct: Option<f64>,
+    indigenous_pct: Option<f64>,
+    new_pct: Option<f64>,
+    gender_non_conforming_pct: Option<f64>,
+}
+
+impl<'a> CsvRow<'a> {
+    fn new(
+        program: &'a ProgramStats,
+        episode: Option<&'a EpisodeStats>,
+        tracks: u32,
+        percentages: Option<ContentPercentages>,
+    ) -> Self {
+        CsvRow {
+            program: &program.name,
+            slug: &program.slug,
+            episode_start: episode.map(|e| e.start),
+            episode_title: episode.and_then(|e| e.title.as_deref()),
+            tracks,
+            australian_pct: percentages.map(|p| p.australian),
+            local_pct: percentages.map(|p| p.local),
+            female_pct: percentages.map(|p| p.female),
+            indigenous_pct: percentages.map(|p| p.indigenous),
+            new_pct: percentages.map(|p| p.new),
+            gender_non_conforming_pct: percentages.map(|p| p.gender_non_conforming),
+        }
+    }
+}
+
+/// Writes the report for all programs. The CSV format has one row per
+/// episode followed by a program total row with an empty `episode_start`.
+pub fn write_report<W: Write>(
+    writer: W,
+    programs: &[ProgramStats],
+    format: ReportFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        ReportFormat::Json => serde_json::to_writer_pretty(writer, programs)?,
+        ReportFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            for program in programs {
+                for episode in &program.episodes {
+                    csv_writer.serialize(CsvRow::new(
+                        program,
+                        Some(episode),
+                        episode.counts.tracks,
+                        episode.percentages,
+                    ))?;
+                }
+                csv_writer.serialize(CsvRow::new(
+                    program,
+                    None,
+                    program.counts.tracks,
+                    program.percentages,
+                ))?;
+            }
+            csv_writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(australian: Option<bool>, female: Option<bool>) -> PlaylistEntry {
+        PlaylistEntry {
+            entry_type: "track".to_owned(),
+            id: 1,
+            artist: Some("artist".to_owned()),
+            title: Some("title".to_owned()),
+            track: Some("title".to_owned()),
+            release: None,
+            time: None,
+            notes: None,
+            content_descriptors: Some(ContentDescriptors {
+                is_australian: australian,
+                is_local: None,
+                is_female: female,
+                is_indigenous: None,
+                is_new: None,
+                is_gender_non_conforming: None,
+            }),
+            approximate_time: None,
+        }
+    }
+
+    #[test]
+    fn test_counts_only_tracks() {
+        let mut break_entry = track(Some(true), Some(true));
+        break_entry.entry_type = "break".to_owned();
+        let counts = DescriptorCounts::from_playlist(&[
+            track(Some(true), Some(false)),
+            track(None, Some(true)),
+            break_entry,
+        ]);
+        assert_eq!(counts.tracks, 2);
+        assert_eq!(counts.australian, 1);
+        assert_eq!(counts.female, 1);
+        assert_eq!(counts.local, 0);
+    }
+
+    #[test]
+    fn test_percentages() {
+        let counts = DescriptorCounts::from_playlist(&[
+            track(Some(true), Some(false)),
+            track(Some(true), Some(true)),
+            track(Some(false), None),
+        ]);
+        let percentages = counts.percentages().unwrap();
+        assert_eq!(percentages.australian, 66.7);
+        assert_eq!(percentages.female, 33.3);
+        assert_eq!(percentages.indigenous, 0.0);
+    }
+
+    #[test]
+    fn test_no_percentages_without_tracks() {
+        assert_eq!(DescriptorCounts::default().percentages(), None);
+    }
+
+    #[test]
+    fn test_description_line() {
+        let counts = DescriptorCounts::from_playlist(&[
+            track(Some(true), Some(false)),
+            track(Some(false), Some(true)),
+        ]);
+        assert_eq!(
+            counts.percentages().unwrap().description_line(),
+            "Content: 50% Australian, 0% local, 50% female, 0% Indigenous, 0% new, 0% gender non-conforming"
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Airnet programs and episodes for unit tests to build on, so that each
+//! test only spells out the fields it cares about.
+
+use crate::airnet::types::{Episode, ProgramDetails};
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+/// The given hour of a day in 2025, in station time.
+pub(crate) fn datetime(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, month, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+}
+
+/// A program named after its slug, with every other field empty.
+pub(crate) fn program(slug: &str) -> ProgramDetails {
+    ProgramDetails {
+        url: None,
+        name: slug.to_owned(),
+        broadcasters: String::new(),
+        description: String::new(),
+        grid_description: None,
+        slug: slug.to_owned(),
+        banner_image_url: None,
+        banner_image_small: None,
+        profile_image_url: String::new(),
+        profile_image_small: String::new(),
+        episodes_rest_url: String::new(),
+    }
+}
+
+/// A two hour episode starting at `start`, untitled and undescribed.
+pub(crate) fn episode(start: NaiveDateTime) -> Episode {
+    Episode {
+        url: None,
+        start,
+        end: start + TimeDelta::hours(2),
+        duration: 7200,
+        multiple_eps_on_day: false,
+        title: None,
+        description: None,
+        image_url: None,
+        current_episode: false,
+        episode_rest_url: "https://rest".to_owned(),
+    }
+}
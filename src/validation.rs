@@ -0,0 +1,397 @@
+use crate::rss::{Item, Rss};
+use chrono::DateTime;
+use reqwest::Url;
+use reqwest::blocking::Client;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long an artwork download may take before it's given up on.
+const ARTWORK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Allowed by RSS 2.0, but podcast directories or clients may reject or mishandle it.
+    Warning,
+    /// Breaks the RSS 2.0 specification, or artwork podcast directories reject.
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: String) -> Self {
+        Issue { severity: Severity::Error, message }
+    }
+
+    fn warning(message: String) -> Self {
+        Issue { severity: Severity::Warning, message }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.message),
+            Severity::Error => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+/// Parses the feed in `file`, failing if it isn't an RSS 2.0 feed at all.
+pub fn read_feed(file: &Path) -> Result<Rss, Box<dyn std::error::Error>> {
+    let xml = std::fs::read_to_string(file)?;
+    Ok(Rss::from_xml(&xml).map_err(|err| format!("{} is not a valid RSS 2.0 feed: {err}", file.display()))?)
+}
+
+/// Parses the feed in `file` and checks it, failing only if it isn't an RSS 2.0 feed at all.
+pub fn validate_file(file: &Path) -> Result<Vec<Issue>, Box<dyn std::error::Error>> {
+    Ok(validate(&read_feed(file)?))
+}
+
+/// As `validate`, also checking the feed's artwork.
+pub fn validate_with_artwork(rss: &Rss, artwork: &mut ArtworkChecker) -> Vec<Issue> {
+    let mut issues = validate(rss);
+    issues.extend(artwork.check(rss));
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    issues
+}
+
+/// Checks the feed against the RSS 2.0 specification and the requirements
+/// of podcast directories, returning errors before warnings.
+pub fn validate(rss: &Rss) -> Vec<Issue> {
+    let channel = rss.channel();
+    let mut issues = Vec::new();
+
+    for (element, value) in [
+        ("title", channel.title()),
+        ("link", channel.link()),
+        ("description", channel.description()),
+    ] {
+        if value.trim().is_empty() {
+            issues.push(Issue::error(format!("channel has no <{element}>")));
+        }
+    }
+    check_url(&mut issues, "channel <link>", channel.link());
+    for (element, date) in [("pubDate", channel.pub_date()), ("lastBuildDate", channel.last_build_date())] {
+        if let Some(date) = date {
+            check_date(&mut issues, &format!("channel <{element}>"), date);
+        }
+    }
+
+    if let Some(image) = channel.image() {
+        check_url(&mut issues, "channel <image> url", image.url());
+        if let (Some(width), Some(height)) = (image.width(), image.height())
+            && width != height
+        {
+            issues.push(Issue::warning(format!("channel <image> is not square ({width}x{height})")));
+        }
+    }
+    match channel.itunes_image() {
+        Some(artwork) => check_url(&mut issues, "channel <itunes:image> href", artwork.href()),
+        None => issues.push(Issue::warning(
+            "channel has no square <itunes:image> artwork for podcast directories".to_owned(),
+        )),
+    }
+    if channel.itunes_explicit().is_none() {
+        issues.push(Issue::warning("channel has no <itunes:explicit> flag".to_owned()));
+    }
+
+    if let Some(live_item) = channel.live_item() {
+        check_url(&mut issues, "channel <podcast:liveItem> enclosure url", live_item.enclosure().url());
+    }
+
+    let mut guids = HashSet::new();
+    for (index, item) in channel.items().iter().enumerate() {
+        let name = item_name(index, item);
+        if item.title().is_none() && item.description().is_none() {
+            issues.push(Issue::error(format!("{name} has neither a <title> nor a <description>")));
+        }
+        if let Some(link) = item.link() {
+            check_url(&mut issues, &format!("{name} <link>"), link);
+        }
+        match item.pub_date() {
+            Some(date) => check_date(&mut issues, &format!("{name} <pubDate>"), date),
+            None => issues.push(Issue::warning(format!("{name} has no <pubDate>"))),
+        }
+        match item.guid() {
+            Some(guid) => {
+                if !guids.insert(guid.value()) {
+                    issues.push(Issue::error(format!("{name} repeats the guid \"{}\"", guid.value())));
+                }
+                if guid.is_permalink() {
+                    check_url(&mut issues, &format!("{name} permalink <guid>"), guid.value());
+                }
+            }
+            None => issues.push(Issue::warning(format!("{name} has no <guid>"))),
+        }
+        match item.enclosure() {
+            Some(enclosure) => {
+                check_url(&mut issues, &format!("{name} <enclosure> url"), enclosure.url());
+                // Airnet doesn't give the size of its recordings, which podcast clients do without.
+                if enclosure.length().is_none() {
+                    issues.push(Issue::warning(format!("{name} <enclosure> has no length")));
+                }
+                if enclosure.media_type().trim().is_empty() {
+                    issues.push(Issue::error(format!("{name} <enclosure> has no type")));
+                }
+                if item.itunes_duration().is_none() {
+                    issues.push(Issue::warning(format!("{name} has no <itunes:duration>")));
+                }
+            }
+            None => issues.push(Issue::warning(format!("{name} has no <enclosure> for podcast clients to play"))),
+        }
+    }
+
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    issues
+}
+
+/// Downloads channels' `<itunes:image>` and checks that it's a square PNG
+/// or JPEG, as podcast directories require. Each image is downloaded once,
+/// however many feeds share it. Artwork that can't be fetched is a warning.
+pub struct ArtworkChecker {
+    client: Client,
+    checked: HashMap<String, Option<Issue>>,
+}
+
+impl Default for ArtworkChecker {
+    fn default() -> Self {
+        let client = Client::builder().timeout(ARTWORK_TIMEOUT).build().expect("TLS backend initializes.");
+        ArtworkChecker { client, checked: HashMap::new() }
+    }
+}
+
+impl ArtworkChecker {
+    pub fn check(&mut self, rss: &Rss) -> Vec<Issue> {
+        let Some(artwork) = rss.channel().itunes_image() else {
+            return Vec::new();
+        };
+        let href = artwork.href();
+        if let Some(issue) = self.checked.get(href) {
+            return issue.iter().cloned().collect();
+        }
+        let image = self
+            .client
+            .get(href)
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .and_then(reqwest::blocking::Response::bytes);
+        let issue = match image {
+            Ok(image) => artwork_issue(href, &image),
+            Err(err) => Some(Issue::warning(format!("channel <itunes:image> \"{href}\" could not be fetched: {err}"))),
+        };
+        self.checked.insert(href.to_owned(), issue.clone());
+        issue.into_iter().collect()
+    }
+}
+
+fn artwork_issue(href: &str, image: &[u8]) -> Option<Issue> {
+    match image_dimensions(image) {
+        Some((width, height)) if width != height => {
+            Some(Issue::error(format!("channel <itunes:image> is not square ({width}x{height})")))
+        }
+        Some(_) => None,
+        None => Some(Issue::error(format!("channel <itunes:image> \"{href}\" is not a PNG or JPEG image"))),
+    }
+}
+
+/// The width and height of a PNG or JPEG image, read from its header.
+fn image_dimensions(image: &[u8]) -> Option<(u32, u32)> {
+    if image.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // The IHDR chunk, which comes first, starts with the width and height.
+        let width = image.get(16..20)?;
+        let height = image.get(20..24)?;
+        return Some((u32::from_be_bytes(width.try_into().ok()?), u32::from_be_bytes(height.try_into().ok()?)));
+    }
+    if !image.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    // Walk the JPEG's segments to its start of frame, which has the height then the width.
+    let mut offset = 2;
+    while let Some(&[0xFF, marker, length_high, length_low]) = image.get(offset..offset + 4) {
+        if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let frame = image.get(offset + 5..offset + 9)?;
+            let height = u16::from_be_bytes([frame[0], frame[1]]);
+            let width = u16::from_be_bytes([frame[2], frame[3]]);
+            return Some((width.into(), height.into()));
+        }
+        offset += 2 + usize::from(u16::from_be_bytes([length_high, length_low]));
+    }
+    None
+}
+
+/// Whether any of the issues breaks the RSS 2.0 specification.
+pub fn has_errors(issues: &[Issue]) -> bool {
+    issues.iter().any(|issue| issue.severity == Severity::Error)
+}
+
+fn item_name(index: usize, item: &Item) -> String {
+    match item.title() {
+        Some(title) => format!("item \"{title}\""),
+        None => format!("item {}", index + 1),
+    }
+}
+
+fn check_url(issues: &mut Vec<Issue>, element: &str, url: &str) {
+    if Url::parse(url).is_err() {
+        issues.push(Issue::error(format!("{element} \"{url}\" is not an absolute URL")));
+    }
+}
+
+fn check_date(issues: &mut Vec<Issue>, element: &str, date: &str) {
+    if DateTime::parse_from_rfc2822(date).is_err() {
+        issues.push(Issue::error(format!("{element} \"{date}\" is not an RFC 822 date")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::{ChannelBuilder, Enclosure, ImageBuilder, ItemBuilder, ItemGuidBuilder, ItunesImage};
+
+    fn item(guid: &str, pub_date: &str) -> Item {
+        ItemBuilder::with_title(guid)
+            .guid(ItemGuidBuilder::new(format!("https://www.pbsfm.org.au/{guid}")).build())
+            .pub_date(pub_date.to_owned())
+            .enclosure(Enclosure::new("https://airnet.org.au/audio.m4a", Some(1024), "audio/mp4"))
+            .itunes_duration(3600)
+            .build()
+    }
+
+    fn feed(items: Vec<Item>) -> Rss {
+        Rss::new(
+            ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "Jazz")
+                .itunes_image(ItunesImage::new("https://profile-img.jpg"))
+                .itunes_explicit(false)
+                .item(items)
+                .build(),
+        )
+    }
+
+    fn messages(issues: Vec<Issue>) -> Vec<String> {
+        issues.iter().map(Issue::to_string).collect()
+    }
+
+    #[test]
+    fn test_valid_feed() {
+        assert_eq!(validate(&feed(vec![item("a", "Mon, 16 Jun 2025 11:00:00 +1000")])), vec![]);
+    }
+
+    #[test]
+    fn test_date_only_pub_date() {
+        assert_eq!(
+            messages(validate(&feed(vec![item("a", "2025-06-16")]))),
+            vec!["error: item \"a\" <pubDate> \"2025-06-16\" is not an RFC 822 date"]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_guids() {
+        let date = "Mon, 16 Jun 2025 11:00:00 +1000";
+        assert_eq!(
+            messages(validate(&feed(vec![item("a", date), item("a", date)]))),
+            vec!["error: item \"a\" repeats the guid \"https://www.pbsfm.org.au/a\""]
+        );
+    }
+
+    #[test]
+    fn test_channel_checks() {
+        let rss = Rss::new(
+            ChannelBuilder::new("", "www.pbsfm.org.au", "Jazz")
+                .image(ImageBuilder::new("https://banner.jpg", "Black Wax", "https://www.pbsfm.org.au").width(144u32).height(100u32).build())
+                .build(),
+        );
+        assert_eq!(
+            messages(validate(&rss)),
+            vec![
+                "error: channel has no <title>",
+                "error: channel <link> \"www.pbsfm.org.au\" is not an absolute URL",
+                "warning: channel <image> is not square (144x100)",
+                "warning: channel has no square <itunes:image> artwork for podcast directories",
+                "warning: channel has no <itunes:explicit> flag",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enclosure_checks() {
+        let rss = feed(vec![
+            ItemBuilder::with_title("a")
+                .enclosure(Enclosure::new("/audio.m4a", None, ""))
+                .build(),
+        ]);
+        assert_eq!(
+            messages(validate(&rss)),
+            vec![
+                "error: item \"a\" <enclosure> url \"/audio.m4a\" is not an absolute URL",
+                "error: item \"a\" <enclosure> has no type",
+                "warning: item \"a\" has no <pubDate>",
+                "warning: item \"a\" has no <guid>",
+                "warning: item \"a\" <enclosure> has no length",
+                "warning: item \"a\" has no <itunes:duration>",
+            ]
+        );
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut image = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        image.extend(width.to_be_bytes());
+        image.extend(height.to_be_bytes());
+        image
+    }
+
+    fn jpeg(width: u16, height: u16) -> Vec<u8> {
+        // Start of image, an APP0 segment, then a baseline start of frame.
+        let mut image = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08];
+        image.extend(height.to_be_bytes());
+        image.extend(width.to_be_bytes());
+        image
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        assert_eq!(image_dimensions(&png(1400, 1400)), Some((1400, 1400)));
+        assert_eq!(image_dimensions(&jpeg(3000, 1500)), Some((3000, 1500)));
+        assert_eq!(image_dimensions(b"GIF89a"), None);
+        assert_eq!(image_dimensions(&png(1400, 1400)[..20]), None);
+    }
+
+    #[test]
+    fn test_artwork_checker_downloads_each_image_once() {
+        let server = httpmock::MockServer::start();
+        let artwork = server.mock(|when, then| {
+            when.path("/art.png");
+            then.status(200).body(png(1400, 1000));
+        });
+        let rss = Rss::new(
+            ChannelBuilder::new("Black Wax", "https://www.pbsfm.org.au/program/black-wax", "Jazz")
+                .itunes_image(ItunesImage::new(server.url("/art.png")))
+                .build(),
+        );
+        let mut checker = ArtworkChecker::default();
+        for _ in 0..2 {
+            let issues: Vec<String> = checker.check(&rss).iter().map(ToString::to_string).collect();
+            assert_eq!(issues, vec!["error: channel <itunes:image> is not square (1400x1000)"]);
+        }
+        artwork.assert_calls(1);
+    }
+
+    #[test]
+    fn test_artwork_issue() {
+        assert_eq!(artwork_issue("https://art.png", &png(1400, 1400)), None);
+        assert_eq!(
+            artwork_issue("https://art.jpg", &jpeg(3000, 1500)).map(|issue| issue.to_string()),
+            Some("error: channel <itunes:image> is not square (3000x1500)".to_owned())
+        );
+        assert_eq!(
+            artwork_issue("https://art.gif", b"GIF89a").map(|issue| issue.to_string()),
+            Some("error: channel <itunes:image> \"https://art.gif\" is not a PNG or JPEG image".to_owned())
+        );
+    }
+}
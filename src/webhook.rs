@@ -0,0 +1,238 @@
+use crate::rss::Item;
+use chrono::{DateTime, SecondsFormat};
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use reqwest::blocking::Client;
+use rss_gen::RssItem;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
+
+/// Header carrying the HMAC-SHA256 of the body, when the webhook has a secret.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// The longest wait between retries, in seconds, however many have failed.
+const MAX_RETRY_DELAY: u64 = 30;
+
+/// The longest an episode's delivery waits between retries in total, in
+/// seconds, so a webhook that is down can't hold up the run or the daemon.
+const MAX_RETRY_TIME: u64 = 60;
+
+/// An endpoint called with each episode newly published in a program feed.
+///
+/// ```toml
+/// [[webhooks]]
+/// url = "https://chat.example/hooks/pbsfm"
+/// secret = "shared-secret"
+/// retries = 3
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+    /// Signs each body in the `X-Signature-256` header as `sha256=<hex HMAC>`.
+    pub secret: Option<String>,
+    /// Attempts after the first before the episode is given up on.
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    /// Seconds before the first retry, doubling for each retry after, up to 30 seconds.
+    /// Retrying stops once a minute has been spent waiting.
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay: u64,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_retry_delay() -> u64 {
+    2
+}
+
+/// The JSON body sent for an episode whose guid wasn't in the previously written feed.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct EpisodePublished {
+    pub event: &'static str,
+    pub program: String,
+    pub program_name: String,
+    pub guid: String,
+    pub title: String,
+    pub air_time: Option<String>,
+    pub enclosure_url: Option<String>,
+    pub link: Option<String>,
+}
+
+impl EpisodePublished {
+    const EVENT: &'static str = "episode.published";
+
+    pub fn from_item(program: &str, program_name: &str, item: &Item) -> Self {
+        EpisodePublished {
+            event: Self::EVENT,
+            program: program.to_owned(),
+            program_name: program_name.to_owned(),
+            guid: item.guid().map(|guid| guid.value()).unwrap_or_default().to_owned(),
+            title: item.title().unwrap_or_default().to_owned(),
+            air_time: item.pub_date().and_then(air_time),
+            enclosure_url: item.enclosure().map(|enclosure| enclosure.url().to_owned()),
+            link: item.link().map(str::to_owned),
+        }
+    }
+
+    /// As `from_item`, for feeds written with the legacy serialization.
+    pub fn from_legacy_item(program: &str, program_name: &str, item: &RssItem) -> Self {
+        let non_empty = |value: &str| Some(value.to_owned()).filter(|value| !value.is_empty());
+        EpisodePublished {
+            event: Self::EVENT,
+            program: program.to_owned(),
+            program_name: program_name.to_owned(),
+            guid: item.guid.clone(),
+            title: item.title.clone(),
+            air_time: air_time(&item.pub_date),
+            enclosure_url: item.enclosure.as_deref().and_then(non_empty),
+            link: non_empty(&item.link),
+        }
+    }
+}
+
+/// An item's RFC 2822 `pubDate` as RFC 3339.
+fn air_time(pub_date: &str) -> Option<String> {
+    let date = DateTime::parse_from_rfc2822(pub_date).ok()?;
+    Some(date.to_rfc3339_opts(SecondsFormat::Secs, false))
+}
+
+/// `sha256=` followed by the hex HMAC-SHA256 of the body.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length.");
+    mac.update(body);
+    let digest: String = mac.finalize().into_bytes().iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("sha256={digest}")
+}
+
+/// Posts the body to the webhook, retrying with backoff until it answers
+/// with a success status, the retries or `MAX_RETRY_TIME` run out, or the
+/// error isn't worth retrying.
+pub fn deliver(client: &Client, webhook: &Webhook, body: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut retry_delays = retry_delays(webhook).into_iter();
+    loop {
+        let mut request = client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .body(body.to_vec());
+        if let Some(secret) = &webhook.secret {
+            request = request.header(SIGNATURE_HEADER, signature(secret, body));
+        }
+        let result = request.send().and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => return Ok(()),
+            Err(err) if !is_retryable(&err) => return Err(err.into()),
+            Err(err) => match retry_delays.next() {
+                Some(delay) => {
+                    eprintln!("Webhook {} failed, retrying in {delay}s: {err}", webhook.url);
+                    std::thread::sleep(Duration::from_secs(delay));
+                }
+                None => return Err(err.into()),
+            },
+        }
+    }
+}
+
+/// Seconds to wait before each of the webhook's retries, leaving out the
+/// retries that would wait past `MAX_RETRY_TIME` in total.
+fn retry_delays(webhook: &Webhook) -> Vec<u64> {
+    let mut waited = 0;
+    (0..webhook.retries)
+        .map(|attempt| retry_delay(webhook.retry_delay, attempt))
+        .take_while(|delay| {
+            waited += delay;
+            waited <= MAX_RETRY_TIME
+        })
+        .collect()
+}
+
+/// Seconds to wait before the retry after `attempt` retries, doubling
+/// `first_delay` each time but never past `MAX_RETRY_DELAY`.
+fn retry_delay(first_delay: u64, attempt: u32) -> u64 {
+    2u64.checked_pow(attempt)
+        .and_then(|factor| first_delay.checked_mul(factor))
+        .map_or(MAX_RETRY_DELAY, |delay| delay.min(MAX_RETRY_DELAY))
+}
+
+/// Whether a failed request may succeed if sent again. Client errors other
+/// than 429 will fail the same way however often they're retried.
+fn is_retryable(err: &reqwest::Error) -> bool {
+    err.status()
+        .is_none_or(|status| !status.is_client_error() || status == StatusCode::TOO_MANY_REQUESTS)
+}
+
+/// Calls every webhook for each newly published episode. The feeds are
+/// already written, so a webhook that fails is reported rather than failing the run.
+pub fn notify(webhooks: &[Webhook], episodes: &[EpisodePublished]) {
+    if webhooks.is_empty() || episodes.is_empty() {
+        return;
+    }
+    let client = Client::new();
+    for episode in episodes {
+        let body = serde_json::to_vec(episode).expect("Episodes serialize to JSON.");
+        for webhook in webhooks {
+            match deliver(&client, webhook, &body) {
+                Ok(()) => println!("Notified {} of new episode: {}", webhook.url, episode.title),
+                Err(err) => eprintln!("Could not notify {} of new episode {}: {err}", webhook.url, episode.title),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rss::{Enclosure, ItemBuilder, ItemGuidBuilder};
+
+    #[test]
+    fn test_signature() {
+        assert_eq!(
+            signature("secret", b"{\"program\":\"black-wax\"}"),
+            "sha256=0f2c6f1b0915f6b2dee1cc19c979b57c9bcf1657cb23c1d5247a89ab9a92b58e"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(2, 0), 2);
+        assert_eq!(retry_delay(2, 3), 16);
+        assert_eq!(retry_delay(2, 4), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(2, 20), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(2, 64), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u64::MAX, 1), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_retry_delays() {
+        let webhook = |retries, retry_delay| Webhook { url: String::new(), secret: None, retries, retry_delay };
+        assert_eq!(retry_delays(&webhook(3, 2)), vec![2, 4, 8]);
+        assert_eq!(retry_delays(&webhook(10, 2)), vec![2, 4, 8, 16, 30]);
+        assert_eq!(retry_delays(&webhook(3, 3600)), vec![30, 30]);
+        assert_eq!(retry_delays(&webhook(0, 2)), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_from_item() {
+        let item = ItemBuilder::with_title("Untitled - 2025-08-25")
+            .link("https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00".to_owned())
+            .guid(ItemGuidBuilder::new("https://airnet/episodes/2025-08-25").is_permalink(false).build())
+            .enclosure(Enclosure::new("https://airnet/audio.m4a", Some(0), "audio/mp4"))
+            .pub_date("Mon, 25 Aug 2025 11:00:00 +1000".to_owned())
+            .build();
+        assert_eq!(
+            serde_json::to_value(EpisodePublished::from_item("black-wax", "Black Wax", &item)).unwrap(),
+            serde_json::json!({
+                "event": "episode.published",
+                "program": "black-wax",
+                "program_name": "Black Wax",
+                "guid": "https://airnet/episodes/2025-08-25",
+                "title": "Untitled - 2025-08-25",
+                "air_time": "2025-08-25T11:00:00+10:00",
+                "enclosure_url": "https://airnet/audio.m4a",
+                "link": "https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00",
+            })
+        );
+    }
+}
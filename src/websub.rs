@@ -0,0 +1,46 @@
+use crate::rss::{AtomLink, Rss};
+use reqwest::blocking::Client;
+
+/// Links the feed to its own URL, which WebSub calls the topic, and to the
+/// hubs subscribers can follow it through.
+/// Links already in the feed, as in one read back from a previous run, aren't repeated.
+pub fn add_links(rss: &mut Rss, feed_url: &str, hubs: &[String]) {
+    let links = std::iter::once(AtomLink::self_link(feed_url, "application/rss+xml"))
+        .chain(hubs.iter().map(|hub| AtomLink::new(hub, "hub")));
+    for link in links {
+        let linked = rss.channel().atom_links().iter().any(|existing| {
+            existing.href() == link.href() && existing.rel() == link.rel()
+        });
+        if !linked {
+            rss.add_atom_link(link);
+        }
+    }
+}
+
+/// Tells the hub the feed at `feed_url` has changed, so it fetches the feed
+/// and notifies subscribers.
+pub fn publish(client: &Client, hub: &str, feed_url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    client
+        .post(hub)
+        .form(&[("hub.mode", "publish"), ("hub.url", feed_url)])
+        .send()?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Pings every hub for each changed feed. The feeds are already written, so
+/// a hub that can't be reached is reported rather than failing the run.
+pub fn publish_changes(hubs: &[String], feed_urls: &[String]) {
+    if hubs.is_empty() || feed_urls.is_empty() {
+        return;
+    }
+    let client = Client::new();
+    for feed_url in feed_urls {
+        for hub in hubs {
+            match publish(&client, hub, feed_url) {
+                Ok(()) => println!("Notified {hub} of change to {feed_url}"),
+                Err(err) => eprintln!("Could not notify {hub} of change to {feed_url}: {err}"),
+            }
+        }
+    }
+}
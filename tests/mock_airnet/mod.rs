@@ -1,5 +1,7 @@
 use httpmock::prelude::*;
+use pbsfm_rss_feed::Args;
 use std::fs;
+use std::path::Path;
 
 pub fn start_mock_airnet_server() -> Result<MockServer, std::io::Error> {
     let all_programs = fs::read_to_string("tests/mock_airnet/responses/all-programs.json")?;
@@ -23,6 +25,19 @@ pub fn start_mock_airnet_server() -> Result<MockServer, std::io::Error> {
         then.status(200).body(episodes);
     });
 
+    server.mock(|when, then| {
+        when.method("GET")
+            .path("/rest/stations/3pbs/programs/black-wax/episodes/2025-06-16+11%3A00%3A00/playlists");
+        then.status(200).body("[]");
+    });
+
+    let playlist = fs::read_to_string("tests/mock_airnet/responses/episode-playlist.json")?;
+    server.mock(|when, then| {
+        when.method("GET")
+            .path("/rest/stations/3pbs/programs/black-wax/episodes/2025-08-25+11%3A00%3A00/playlists");
+        then.status(200).body(playlist);
+    });
+
     server.mock(|when, then| {
         when.method("GET").any_request();
         then.status(404).body("Not Found");
@@ -31,11 +46,42 @@ pub fn start_mock_airnet_server() -> Result<MockServer, std::io::Error> {
     Ok(server)
 }
 
+/// Arguments writing Black Wax's feed, fetched from `airnet_url`, to
+/// `output_dir` with every optional output off.
+#[allow(dead_code)]
+pub fn base_args(output_dir: &Path, airnet_url: String) -> Args {
+    Args {
+        airnet_url,
+        programs: vec!("black-wax".to_string()),
+        output_dir: output_dir.to_path_buf(),
+        use_custom_rss_serialization: false,
+        content_stats_in_description: false,
+        stats_format: None,
+        combined_feed: false,
+        combined_feed_days: 7,
+        presenter_feeds: false,
+        genre_feeds: false,
+        changelog_feed: false,
+        config: None,
+        archive: None,
+        feed_from_archive: false,
+        strict: false,
+        live_stream_url: None,
+        public_base_url: None,
+        websub_hubs: vec![],
+        site_dir: None,
+        feed_stylesheet: false,
+        extra_formats: vec![],
+        retirement_item: false,
+        site_program_pages_only: false,
+    }
+}
+
 pub mod expected {
     use chrono::NaiveDate;
     use pbsfm_rss_feed::airnet::types::{Episode, ProgramDescription, ProgramDetails};
     use pbsfm_rss_feed::rss;
-    use pbsfm_rss_feed::rss::{Enclosure, ImageBuilder, Item, ItemBuilder, ItemGuidBuilder};
+    use pbsfm_rss_feed::rss::{Enclosure, ImageBuilder, Item, ItemBuilder, ItemGuidBuilder, PodcastPerson};
     use rss_gen::{RssData, RssItem, RssVersion};
 
     #[allow(dead_code)]
@@ -82,6 +128,7 @@ pub mod expected {
         }
     }
 
+    #[allow(dead_code)]
     pub fn episodes() -> Vec<Episode> {
         vec![
             Episode {
@@ -95,11 +142,13 @@ pub mod expected {
                     .and_hms_opt(13, 0, 0)
                     .unwrap(),
                 duration: 7200,
+                multiple_eps_on_day: false,
                 title: Some(String::from(
                     "Interview with Vince Jones and Jacob Collier!",
                 )),
                 description: None,
                 image_url: None,
+                current_episode: false,
                 episode_rest_url: String::from(
                     "https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-06-16+11%3A00%3A00",
                 ),
@@ -115,9 +164,11 @@ pub mod expected {
                     .and_hms_opt(13, 0, 0)
                     .unwrap(),
                 duration: 7200,
+                multiple_eps_on_day: false,
                 title: None,
                 description: Some(String::from("some description")),
                 image_url: Some(String::from("http://img-url")),
+                current_episode: true,
                 episode_rest_url: String::from(
                     "https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-08-25+11%3A00%3A00",
                 ),
@@ -163,11 +214,13 @@ pub mod expected {
 
         let rss_feed = rss::Rss::new(
             rss::ChannelBuilder::new(&program.name, program_link, &program.description)
-                .category(rss::CategoryBuilder::new(category).build())
+                .category(vec![rss::CategoryBuilder::new(category).build()])
                 .image(
                     ImageBuilder::new("https://profile-img.jpg", &program.name, program_link)
                         .build(),
                 )
+                .itunes_image(rss::ItunesImage::new("https://profile-img.jpg"))
+                .itunes_explicit(false)
                 .language("en".to_owned())
                 .item(rss_items_v2(&program))
                 .build(),
@@ -181,19 +234,19 @@ pub mod expected {
             RssItem::new()
                 .title(String::from("Interview with Vince Jones and Jacob Collier!"))
                 .link("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00")
-                .guid("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00")
+                .guid("https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-06-16+11%3A00%3A00")
                 .author(&program.broadcasters)
                 .description("")
                 .enclosure("https://airnet.org.au/omnystudio/3pbs/black-wax/2025-06-16+11:00:00/aac_mid.m4a")
-                .pub_date("2025-06-16"),
+                .pub_date("Mon, 16 Jun 2025 11:00:00 +1000"),
             RssItem::new()
                 .title(String::from("Untitled - 2025-08-25"))
                 .link("https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00")
-                .guid("https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00")
+                .guid("https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-08-25+11%3A00%3A00")
                 .author(&program.broadcasters)
                 .description("some description")
                 .enclosure("https://airnet.org.au/omnystudio/3pbs/black-wax/2025-08-25+11:00:00/aac_mid.m4a")
-                .pub_date("2025-08-25"),
+                .pub_date("Mon, 25 Aug 2025 11:00:00 +1000"),
         )
     }
 
@@ -202,18 +255,20 @@ pub mod expected {
         vec!(
             ItemBuilder::with_title("Interview with Vince Jones and Jacob Collier!")
                 .link("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00".to_owned())
-                .guid(ItemGuidBuilder::new("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00").build())
+                .guid(ItemGuidBuilder::new("https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-06-16+11%3A00%3A00").is_permalink(false).build())
                 .author(program.broadcasters.clone())
                 .enclosure(Enclosure::new(
                     "https://airnet.org.au/omnystudio/3pbs/black-wax/2025-06-16+11:00:00/aac_mid.m4a",
                     None,
                     "audio/mp4"
                 ))
-                .pub_date("2025-06-16".to_owned())
+                .pub_date("Mon, 16 Jun 2025 11:00:00 +1000".to_owned())
+                .itunes_duration(7200u32)
+                .podcast_person(vec![PodcastPerson::host("Adam Rudegeair")])
                 .build(),
             ItemBuilder::with_title("Untitled - 2025-08-25")
                 .link("https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00".to_owned())
-                .guid(ItemGuidBuilder::new("https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00").build())
+                .guid(ItemGuidBuilder::new("https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-08-25+11%3A00%3A00").is_permalink(false).build())
                 .author(program.broadcasters.clone())
                 .description("some description".to_string())
                 .enclosure(Enclosure::new(
@@ -221,7 +276,9 @@ pub mod expected {
                     None,
                     "audio/mp4"
                 ))
-                .pub_date("2025-08-25".to_owned())
+                .pub_date("Mon, 25 Aug 2025 11:00:00 +1000".to_owned())
+                .itunes_duration(7200u32)
+                .podcast_person(vec![PodcastPerson::host("Adam Rudegeair")])
                 .build()
         )
     }
@@ -53,4 +53,38 @@ fn test_fails_when_not_found() -> Result<(), Box<dyn std::error::Error>> {
     // TODO check correct message
     assert!(result.is_err());
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_get_episode_playlist() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+
+    let client = airnet::AirnetClient::new(server.base_url());
+    let start = mock_airnet::expected::episodes()[1].start;
+    let playlist = client.playlist("3pbs", "black-wax", &start)?;
+
+    assert_eq!(playlist.len(), 19);
+    assert_eq!(playlist[2].artist.as_deref(), Some("Paul Grabowsky"));
+    assert_eq!(playlist[2].release.as_deref(), Some("Tales of Time and Space"));
+    assert_eq!(
+        playlist[2].content_descriptors.as_ref().and_then(|d| d.is_australian),
+        Some(true)
+    );
+    assert_eq!(playlist[0].approximate_time, Some(start));
+    Ok(())
+}
+
+#[test]
+fn test_playlist_cache_tells_fetched_from_inserted() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+
+    let client = airnet::AirnetClient::new(server.base_url());
+    let episodes = mock_airnet::expected::episodes();
+    let mut playlists = airnet::PlaylistCache::default();
+    playlists.insert("black-wax", &episodes[0].start, Vec::new());
+    playlists.fetch(&client, "3pbs", "black-wax", &episodes[1].start)?;
+
+    let fetched: Vec<_> = playlists.fetched().map(|((_, start), _)| *start).collect();
+    assert_eq!(fetched, vec![episodes[1].start]);
+    Ok(())
+}
@@ -1,4 +1,10 @@
 use pbsfm_rss_feed::Args;
+use pbsfm_rss_feed::airnet::types::Episode;
+use pbsfm_rss_feed::archive::Archive;
+use pbsfm_rss_feed::feed_format::FeedFormat;
+use pbsfm_rss_feed::manifest;
+use pbsfm_rss_feed::stats::ReportFormat;
+use chrono::{TimeZone, Utc};
 use tempdir::TempDir;
 use std::fs;
 
@@ -8,12 +14,7 @@ mod mock_airnet;
 fn test_cli_e2e() -> Result<(), Box<dyn std::error::Error>> {
     let server = mock_airnet::start_mock_airnet_server()?;
     let tmp_dir = TempDir::new("output")?;
-    let args = Args {
-        airnet_url: server.base_url(),
-        programs: vec!("black-wax".to_string()),
-        output_dir: tmp_dir.path().to_path_buf(),
-        use_custom_rss_serialization: false,
-    };
+    let args = mock_airnet::base_args(tmp_dir.path(), server.base_url());
     pbsfm_rss_feed::run_app(args)?;
 
     let output_file = tmp_dir.path().join("pbsfm/black-wax/rss.xml");
@@ -23,15 +24,33 @@ fn test_cli_e2e() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_cli_e2e_writes_extra_formats_of_legacy_feeds() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        extra_formats: vec![FeedFormat::Atom, FeedFormat::Json],
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let feed_dir = tmp_dir.path().join("pbsfm/black-wax");
+    let atom = fs::read_to_string(feed_dir.join("atom.xml"))?;
+    assert!(atom.contains("<title>Interview with Vince Jones and Jacob Collier!</title>"));
+    let json: serde_json::Value = serde_json::from_slice(&fs::read(feed_dir.join("feed.json"))?)?;
+    assert_eq!(json["title"], "Black Wax");
+    assert_eq!(json["items"].as_array().map(Vec::len), Some(2));
+    Ok(())
+}
+
 #[test]
 fn test_cli_e2e_with_custom_rss_serialization() -> Result<(), Box<dyn std::error::Error>> {
     let server = mock_airnet::start_mock_airnet_server()?;
     let tmp_dir = TempDir::new("output")?;
     let args = Args {
-        airnet_url: server.base_url(),
-        programs: vec!("black-wax".to_string()),
-        output_dir: tmp_dir.path().to_path_buf(),
         use_custom_rss_serialization: true,
+        strict: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
     };
     pbsfm_rss_feed::run_app(args)?;
 
@@ -40,4 +59,592 @@ fn test_cli_e2e_with_custom_rss_serialization() -> Result<(), Box<dyn std::error
     let expected_contents = fs::read_to_string("tests/expected-black-wax-v2.rss")?;
     assert_eq!(contents, expected_contents);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_cli_e2e_keeps_published_guids() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let feed_dir = tmp_dir.path().join("pbsfm/black-wax");
+    fs::create_dir_all(&feed_dir)?;
+    // Written before guids came from Airnet, with the episode link as the guid.
+    fs::copy("tests/published-black-wax-v2.rss", feed_dir.join("rss.xml"))?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        strict: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let contents = fs::read_to_string(feed_dir.join("rss.xml"))?;
+    assert!(contents.contains("<guid>https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00</guid>"));
+    assert!(contents.contains("<guid>https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00</guid>"));
+    assert!(!contents.contains("isPermaLink"));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_while_episode_airing() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        strict: true,
+        live_stream_url: Some("https://stream.example/3pbs.aac".to_string()),
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    // 12:00 in Melbourne, halfway through the 2025-08-25 episode.
+    pbsfm_rss_feed::run_app_at(args, Utc.with_ymd_and_hms(2025, 8, 25, 2, 0, 0).unwrap())?;
+
+    let contents = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax/rss.xml"))?;
+    assert_eq!(contents.matches("<item>").count(), 1);
+    assert!(!contents.contains("<guid>https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00</guid>"));
+    assert!(contents.contains(
+        "<podcast:liveItem status=\"live\" start=\"2025-08-25T11:00:00+10:00\" end=\"2025-08-25T13:00:00+10:00\">\n      \
+        <title>Untitled - 2025-08-25</title>"
+    ));
+    assert!(contents.contains("<enclosure url=\"https://stream.example/3pbs.aac\" length=\"0\" type=\"audio/aac\"/>"));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        content_stats_in_description: true,
+        stats_format: Some(ReportFormat::Csv),
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let feed = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax/rss.xml"))?;
+    assert!(feed.contains(
+        "<description>some description\n\nContent: 42% Australian, 0% local, 21% female, 0% Indigenous, 0% new, 0% gender non-conforming</description>"
+    ));
+
+    let report = fs::read_to_string(tmp_dir.path().join("pbsfm/stats.csv"))?;
+    assert_eq!(
+        report,
+        "program,slug,episode_start,episode_title,tracks,australian_pct,local_pct,female_pct,indigenous_pct,new_pct,gender_non_conforming_pct\n\
+        Black Wax,black-wax,2025-06-16T11:00:00,Interview with Vince Jones and Jacob Collier!,0,,,,,,\n\
+        Black Wax,black-wax,2025-08-25T11:00:00,,19,42.1,0.0,21.1,0.0,0.0,0.0\n\
+        Black Wax,black-wax,,,19,42.1,0.0,21.1,0.0,0.0,0.0\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_combined_feed() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        combined_feed: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    // The day after the last episode, whose week the feed covers.
+    pbsfm_rss_feed::run_app_at(args, Utc.with_ymd_and_hms(2025, 8, 26, 2, 0, 0).unwrap())?;
+
+    let feed = fs::read_to_string(tmp_dir.path().join("pbsfm/all/rss.xml"))?;
+    assert!(feed.contains("<title>Black Wax: Untitled - 2025-08-25</title>"));
+    assert!(!feed.contains("Interview with Vince Jones"));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_feed_stylesheet() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    for use_custom_rss_serialization in [false, true] {
+        let tmp_dir = TempDir::new("output")?;
+        let args = Args {
+            use_custom_rss_serialization,
+            combined_feed: true,
+            strict: true,
+            feed_stylesheet: true,
+            ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+        };
+        pbsfm_rss_feed::run_app(args)?;
+
+        for feed_path in ["pbsfm/black-wax/rss.xml", "pbsfm/all/rss.xml"] {
+            let feed = fs::read_to_string(tmp_dir.path().join(feed_path))?;
+            assert!(feed.starts_with(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<?xml-stylesheet type=\"text/xsl\" href=\"../../feed.xsl\"?>\n<rss"
+            ));
+        }
+        let stylesheet = fs::read_to_string(tmp_dir.path().join("feed.xsl"))?;
+        assert!(stylesheet.contains("<xsl:stylesheet"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_writes_manifest_and_opml() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        combined_feed: true,
+        strict: true,
+        public_base_url: Some("https://example.com/feeds/".to_string()),
+        extra_formats: vec![FeedFormat::Json],
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    assert!(tmp_dir.path().join("pbsfm/black-wax/feed.json").exists());
+    let manifest: serde_json::Value = serde_json::from_slice(&fs::read(tmp_dir.path().join("index.json"))?)?;
+    let feed = fs::read(tmp_dir.path().join("pbsfm/black-wax/rss.xml"))?;
+    assert_eq!(
+        manifest,
+        serde_json::json!({
+            "programs": [{
+                "slug": "black-wax",
+                "title": "Black Wax",
+                "link": "https://www.pbsfm.org.au/program/black-wax",
+                "category": "Groovin' jazz",
+                "artwork_url": "https://profile-img.jpg",
+                "feeds": {
+                    "json": "https://example.com/feeds/pbsfm/black-wax/feed.json",
+                    "rss": "https://example.com/feeds/pbsfm/black-wax/rss.xml",
+                },
+                "item_count": 2,
+                "newest_episode": "2025-08-25T11:00:00+10:00",
+                "content_hash": manifest::content_hash(&feed),
+                "retired": null,
+            }]
+        })
+    );
+
+    let feed = String::from_utf8(feed)?;
+    assert!(feed.contains(
+        r#"<atom:link href="https://example.com/feeds/pbsfm/black-wax/rss.xml" rel="self" type="application/rss+xml"/>"#
+    ));
+    // Only a feed that has moved names a new feed URL.
+    assert!(!feed.contains("itunes:new-feed-url"));
+    let json_feed = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax/feed.json"))?;
+    assert!(json_feed.contains(r#""feed_url": "https://example.com/feeds/pbsfm/black-wax/feed.json""#));
+    let opml = fs::read_to_string(tmp_dir.path().join("index.opml"))?;
+    assert!(opml.contains(
+        r#"<outline type="rss" text="Black Wax" title="Black Wax" xmlUrl="https://example.com/feeds/pbsfm/black-wax/rss.xml" htmlUrl="https://www.pbsfm.org.au/program/black-wax"/>"#
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_rejects_redirecting_a_generated_program() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let config_path = tmp_dir.path().join("config.toml");
+    fs::write(&config_path, "[redirects]\nblack-wax = \"black-wax-classic\"\n")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        public_base_url: Some("https://example.com/feeds/".to_owned()),
+        config: Some(config_path),
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    assert!(pbsfm_rss_feed::run_app(args).is_err());
+    assert!(!tmp_dir.path().join("pbsfm/black-wax/rss.xml").exists());
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_redirects() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let config_path = tmp_dir.path().join("feeds.toml");
+    fs::write(&config_path, "[redirects]\nblack-wax-classic = \"black-wax\"\n")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        config: Some(config_path),
+        strict: true,
+        public_base_url: Some("https://example.com/feeds".to_string()),
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let stub = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax-classic/rss.xml"))?;
+    assert!(stub.contains("<itunes:new-feed-url>https://example.com/feeds/pbsfm/black-wax/rss.xml</itunes:new-feed-url>"));
+    assert!(stub.contains(
+        r#"<atom:link href="https://example.com/feeds/pbsfm/black-wax-classic/rss.xml" rel="self" type="application/rss+xml"/>"#
+    ));
+    assert!(!stub.contains("<item>"));
+    let page = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax-classic/index.html"))?;
+    assert!(page.contains(r#"<meta http-equiv="refresh" content="0; url=https://example.com/feeds/pbsfm/black-wax/rss.xml">"#));
+
+    let manifest = fs::read_to_string(tmp_dir.path().join("index.json"))?;
+    assert!(!manifest.contains("\"slug\": \"black-wax-classic\""));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_retires_programs_gone_from_airnet() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let feed_dir = tmp_dir.path().join("pbsfm/black-wax-lounge");
+    fs::create_dir_all(&feed_dir)?;
+    fs::copy("tests/published-black-wax-v2.rss", feed_dir.join("rss.xml"))?;
+    let args = Args {
+        programs: vec!("black-wax-lounge".to_string()),
+        use_custom_rss_serialization: true,
+        strict: true,
+        retirement_item: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args.clone())?;
+
+    let feed = fs::read_to_string(feed_dir.join("rss.xml"))?;
+    assert!(feed.contains("<itunes:complete>Yes</itunes:complete>"));
+    assert!(feed.contains("<podcast:locked>yes</podcast:locked>"));
+    assert!(feed.contains("<title>Black Wax has ended</title>"));
+    assert!(feed.contains("Interview with Vince Jones and Jacob Collier!"));
+
+    let manifest: serde_json::Value = serde_json::from_slice(&fs::read(tmp_dir.path().join("index.json"))?)?;
+    let retired = manifest["programs"][0]["retired"].as_str().expect("retirement date");
+
+    pbsfm_rss_feed::run_app(args)?;
+    assert_eq!(fs::read_to_string(feed_dir.join("rss.xml"))?, feed);
+    let manifest: serde_json::Value = serde_json::from_slice(&fs::read(tmp_dir.path().join("index.json"))?)?;
+    assert_eq!(manifest["programs"][0]["retired"], retired);
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_site() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        site_dir: Some(tmp_dir.path().to_path_buf()),
+        ..mock_airnet::base_args(&tmp_dir.path().join("feeds"), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let index = fs::read_to_string(tmp_dir.path().join("index.html"))?;
+    assert!(index.contains(r#"<strong><a href="pbsfm/black-wax/">Black Wax</a></strong> - Groovin&#x27; jazz"#));
+    assert!(index.contains(r#"<a href="feeds/pbsfm/black-wax/rss.xml">"#));
+
+    let program_page = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax/index.html"))?;
+    assert!(program_page.contains(r#"href="../../feeds/pbsfm/black-wax/rss.xml""#));
+    assert!(program_page.contains("Interview with Vince Jones and Jacob Collier!"));
+    assert!(program_page.contains("Tracklist (19 tracks)"));
+    assert!(program_page.contains("<strong>Robert Glasper</strong> - Say Less <em>(Code Derivations)</em>"));
+
+    assert!(tmp_dir.path().join("assets/css/main.css").exists());
+    assert!(tmp_dir.path().join("assets/images/rss.png").exists());
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_refreshes_site_program_pages() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        site_dir: Some(tmp_dir.path().to_path_buf()),
+        ..mock_airnet::base_args(&tmp_dir.path().join("feeds"), server.base_url())
+    };
+    // As the daemon refreshes a program whose episode just ended.
+    pbsfm_rss_feed::run_app(args.without_station_outputs(vec!["black-wax".to_owned()]))?;
+
+    let program_page = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax/index.html"))?;
+    assert!(program_page.contains("Interview with Vince Jones and Jacob Collier!"));
+    // The index lists every program, so it's left to the full runs.
+    assert!(!tmp_dir.path().join("index.html").exists());
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_presenter_feeds() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        presenter_feeds: true,
+        strict: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let feed = fs::read_to_string(tmp_dir.path().join("pbsfm/presenters/adam-rudegeair/rss.xml"))?;
+    assert!(feed.contains("<title>PBS 106.7FM - Adam Rudegeair</title>"));
+    assert!(feed.contains("<title>Black Wax: Interview with Vince Jones and Jacob Collier!</title>"));
+    assert!(feed.contains(r#"<podcast:person role="host">Adam Rudegeair</podcast:person>"#));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_genres() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let config_path = tmp_dir.path().join("feeds.toml");
+    fs::write(
+        &config_path,
+        r#"
+[[genres]]
+slug = "jazz"
+name = "Jazz"
+apple_category = "Music"
+apple_subcategory = "Music Commentary"
+grid_descriptions = ["jazz"]
+"#,
+    )?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        genre_feeds: true,
+        config: Some(config_path),
+        strict: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args.clone())?;
+    // The second run reads back the first run's feed, nested itunes:category and all.
+    pbsfm_rss_feed::run_app(args)?;
+
+    let feed = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax/rss.xml"))?;
+    assert!(feed.contains("<category>Groovin' jazz</category>"));
+    assert!(feed.contains(r#"<category domain="https://github.com/aringeri/pbsfm-rss-feed#genres">Jazz</category>"#));
+    assert!(feed.contains(
+        "<itunes:category text=\"Music\">\n      <itunes:category text=\"Music Commentary\"/>\n    </itunes:category>"
+    ));
+
+    let genre_feed = fs::read_to_string(tmp_dir.path().join("pbsfm/genres/jazz/rss.xml"))?;
+    assert!(genre_feed.contains("<title>PBS 106.7FM - Jazz</title>"));
+    assert!(genre_feed.contains(r#"<category domain="https://github.com/aringeri/pbsfm-rss-feed#genres">Jazz</category>"#));
+    assert!(genre_feed.contains("<title>Black Wax: Interview with Vince Jones and Jacob Collier!</title>"));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_rejects_genres_without_custom_serialization() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let config_path = tmp_dir.path().join("config.toml");
+    fs::write(&config_path, "[[genres]]\nslug = \"jazz\"\nname = \"Jazz\"\napple_category = \"Music\"\n")?;
+    let args = Args { config: Some(config_path), ..mock_airnet::base_args(tmp_dir.path(), server.base_url()) };
+    assert!(pbsfm_rss_feed::run_app(args).is_err());
+    assert!(!tmp_dir.path().join("pbsfm/black-wax/rss.xml").exists());
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_changelog_feed() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        changelog_feed: true,
+        strict: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args.clone())?;
+
+    let changelog_dir = tmp_dir.path().join("pbsfm/changelog");
+    let feed = fs::read_to_string(changelog_dir.join("rss.xml"))?;
+    assert!(!feed.contains("<item>"));
+    let mut snapshot: serde_json::Value = serde_json::from_slice(&fs::read(changelog_dir.join("all-programs.json"))?)?;
+    assert_eq!(snapshot[1]["broadcasters"], "Adam Rudegeair");
+
+    snapshot[1]["broadcasters"] = "Someone Else".into();
+    fs::write(changelog_dir.join("all-programs.json"), serde_json::to_vec(&snapshot)?)?;
+    pbsfm_rss_feed::run_app(args)?;
+
+    let feed = fs::read_to_string(changelog_dir.join("rss.xml"))?;
+    assert!(feed.contains("<title>New presenters for Black Wax</title>"));
+    assert!(feed.contains("Black Wax is now presented by Adam Rudegeair, previously Someone Else."));
+    let manifest = fs::read_to_string(tmp_dir.path().join("index.json"))?;
+    assert!(!manifest.contains("\"slug\": \"changelog\""));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_smart_feeds() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let config_path = tmp_dir.path().join("feeds.toml");
+    fs::write(&config_path, r#"
+[[smart_feeds]]
+name = "Interviews"
+slug = "interviews"
+filter = "title ~ /interview/i"
+
+[[smart_feeds]]
+name = "Australian jazz"
+slug = "australian-jazz"
+programs = ["black-wax"]
+filter = "artist ~ /grabowsky/i and australian >= 40"
+"#)?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        config: Some(config_path),
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let interviews = fs::read_to_string(tmp_dir.path().join("pbsfm/smart/interviews/rss.xml"))?;
+    assert!(interviews.contains("<title>PBS 106.7FM - Interviews</title>"));
+    assert!(interviews.contains("<title>Black Wax: Interview with Vince Jones and Jacob Collier!</title>"));
+    assert!(!interviews.contains("Untitled - 2025-08-25"));
+
+    let australian_jazz = fs::read_to_string(tmp_dir.path().join("pbsfm/smart/australian-jazz/rss.xml"))?;
+    assert!(australian_jazz.contains("<title>Black Wax: Untitled - 2025-08-25</title>"));
+    assert!(!australian_jazz.contains("Interview with Vince Jones"));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_artist_feeds() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let config_path = tmp_dir.path().join("feeds.toml");
+    fs::write(&config_path, r#"
+[artist_feeds]
+artists = ["Vince Jones", "Vince-Jones"]
+top = 1
+"#)?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        config: Some(config_path),
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let vince_jones = fs::read_to_string(tmp_dir.path().join("pbsfm/artists/vince-jones/rss.xml"))?;
+    assert!(vince_jones.contains("<title>PBS 106.7FM - Vince Jones</title>"));
+    assert!(vince_jones.contains("<title>Black Wax: Untitled - 2025-08-25</title>"));
+    assert!(vince_jones.contains(
+        "<description>Tracks by Vince Jones in this episode:\nPaul Grabowsky and Vince Jones - This Is Always (Provenance)</description>"
+    ));
+
+    let top_artist = fs::read_to_string(tmp_dir.path().join("pbsfm/artists/paul-grabowsky/rss.xml"))?;
+    assert!(top_artist.contains("<title>PBS 106.7FM - Paul Grabowsky</title>"));
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_archive() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let tmp_dir = TempDir::new("output")?;
+    let archive_path = tmp_dir.path().join("archive.sqlite");
+    let args = Args {
+        use_custom_rss_serialization: true,
+        stats_format: Some(ReportFormat::Json),
+        archive: Some(archive_path.clone()),
+        feed_from_archive: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    let archive = Archive::open(&archive_path)?;
+    assert_eq!(archive.program("black-wax")?, Some(mock_airnet::expected::single_program()));
+    // Whether an episode is current is only true when it is fetched, so it isn't archived.
+    let episodes: Vec<_> = mock_airnet::expected::episodes()
+        .into_iter()
+        .map(|episode| Episode { current_episode: false, ..episode })
+        .collect();
+    assert_eq!(archive.episodes("black-wax")?, episodes);
+    let start = mock_airnet::expected::episodes()[1].start;
+    assert_eq!(archive.playlist("black-wax", &start)?.len(), 19);
+
+    let contents = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax/rss.xml"))?;
+    let expected_contents = fs::read_to_string("tests/expected-black-wax-v2.rss")?;
+    assert_eq!(contents, expected_contents);
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_websub() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let hub = httpmock::MockServer::start();
+    let feed_url = "https://feeds.example/pbsfm/black-wax/rss.xml";
+    let publish = hub.mock(|when, then| {
+        when.method(httpmock::Method::POST)
+            .path("/hub")
+            .form_urlencoded_tuple("hub.mode", "publish")
+            .form_urlencoded_tuple("hub.url", feed_url);
+        then.status(204);
+    });
+    let other_publish = hub.mock(|when, then| {
+        when.method(httpmock::Method::POST).path("/hub");
+        then.status(204);
+    });
+    let tmp_dir = TempDir::new("output")?;
+    let args = || Args {
+        use_custom_rss_serialization: true,
+        strict: true,
+        public_base_url: Some("https://feeds.example/".to_owned()),
+        websub_hubs: vec![hub.url("/hub")],
+        extra_formats: vec![FeedFormat::Atom, FeedFormat::Json],
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args())?;
+
+    let contents = fs::read_to_string(tmp_dir.path().join("pbsfm/black-wax/rss.xml"))?;
+    assert!(contents.contains("xmlns:atom=\"http://www.w3.org/2005/Atom\""));
+    assert!(contents.contains(&format!("<atom:link href=\"{feed_url}\" rel=\"self\" type=\"application/rss+xml\"/>")));
+    assert!(contents.contains(&format!("<atom:link href=\"{}\" rel=\"hub\"/>", hub.url("/hub"))));
+    // The Atom and JSON feeds don't link to the hub, so only the RSS feed is published.
+    publish.assert_calls(1);
+    other_publish.assert_calls(0);
+
+    // Nothing changed, so the hub isn't pinged again.
+    pbsfm_rss_feed::run_app(args())?;
+    publish.assert_calls(1);
+    Ok(())
+}
+
+#[test]
+fn test_cli_e2e_with_webhooks() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let hooks = httpmock::MockServer::start();
+    let body = serde_json::json!({
+        "event": "episode.published",
+        "program": "black-wax",
+        "program_name": "Black Wax",
+        "guid": "https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-08-25+11%3A00%3A00",
+        "title": "Untitled - 2025-08-25",
+        "air_time": "2025-08-25T11:00:00+10:00",
+        "enclosure_url": "https://airnet.org.au/omnystudio/3pbs/black-wax/2025-08-25+11:00:00/aac_mid.m4a",
+        "link": "https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00",
+    });
+    let signed = hooks.mock(|when, then| {
+        when.method(httpmock::Method::POST)
+            .path("/signed")
+            .json_body(body.clone())
+            .is_true(|request| {
+                let expected = pbsfm_rss_feed::webhook::signature("secret", &request.body().to_vec());
+                request.headers().get("X-Signature-256").is_some_and(|signature| signature == expected.as_str())
+            });
+        then.status(200);
+    });
+    let failing = hooks.mock(|when, then| {
+        when.method(httpmock::Method::POST).path("/failing");
+        then.status(500);
+    });
+
+    let tmp_dir = TempDir::new("output")?;
+    let feed_dir = tmp_dir.path().join("pbsfm/black-wax");
+    fs::create_dir_all(&feed_dir)?;
+    // The previous feed, written before the 2025-08-25 episode was published.
+    let published = fs::read_to_string("tests/published-black-wax-v2.rss")?;
+    let (before, after) = published.split_at(published.find("    <item>\n      <title>Untitled - 2025-08-25").unwrap());
+    fs::write(feed_dir.join("rss.xml"), format!("{before}{}", &after[after.find("</item>\n").unwrap() + 8..]))?;
+
+    let config_path = tmp_dir.path().join("config.toml");
+    fs::write(&config_path, format!(
+        "[[webhooks]]\nurl = \"{}\"\nsecret = \"secret\"\n\n[[webhooks]]\nurl = \"{}\"\nretries = 2\nretry_delay = 0\n",
+        hooks.url("/signed"),
+        hooks.url("/failing"),
+    ))?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        config: Some(config_path),
+        strict: true,
+        ..mock_airnet::base_args(tmp_dir.path(), server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)?;
+
+    signed.assert_calls(1);
+    // The first attempt and two retries, without failing the run.
+    failing.assert_calls(3);
+    Ok(())
+}
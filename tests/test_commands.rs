@@ -0,0 +1,142 @@
+use chrono::{TimeZone, Utc};
+use clap::Parser;
+use pbsfm_rss_feed::airnet::{AirnetClient, PlaylistCache};
+use pbsfm_rss_feed::commands::{self, ValidateArgs};
+use pbsfm_rss_feed::search::OutputFormat;
+use pbsfm_rss_feed::stats::{self, ReportFormat};
+use pbsfm_rss_feed::validation;
+use pbsfm_rss_feed::{Cli, Command};
+use std::path::Path;
+
+mod mock_airnet;
+
+#[test]
+fn test_parse_subcommands() {
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "generate", "--programs", "black-wax"]).unwrap();
+    assert!(matches!(cli.command, Some(Command::Generate(args)) if args.programs == vec!["black-wax"]));
+
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "list-programs", "--format", "json"]).unwrap();
+    assert!(matches!(cli.command, Some(Command::ListPrograms(args)) if args.format == OutputFormat::Json));
+
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "show", "black-wax", "-e", "2"]).unwrap();
+    assert!(matches!(cli.command, Some(Command::Show(args)) if args.slug == "black-wax" && args.episodes == 2));
+
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "stats", "--programs", "black-wax", "--format", "csv"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Some(Command::Stats(args)) if args.programs == vec!["black-wax"] && args.format == ReportFormat::Csv && args.output.is_none()
+    ));
+
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "validate", "rss.xml"]).unwrap();
+    assert!(matches!(cli.command, Some(Command::Validate(args)) if args.file == Path::new("rss.xml")));
+
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "daemon", "--programs", "black-wax", "--refresh-delay", "30"]).unwrap();
+    assert!(matches!(
+        cli.command,
+        Some(Command::Daemon(args)) if args.args.programs == vec!["black-wax"] && args.refresh_delay == 30 && args.jitter == 300
+    ));
+    // A full refresh every 0 hours would run back to back against Airnet.
+    for invalid in [["--full-refresh-hours", "0"], ["--refresh-delay", "-5"], ["--jitter", "-1"]] {
+        let daemon = ["pbsfm-rss-feed", "daemon", "--programs", "black-wax"];
+        assert!(Cli::try_parse_from([&daemon[..], &invalid[..]].concat()).is_err());
+    }
+
+    let live_without_custom_serialization =
+        ["pbsfm-rss-feed", "generate", "--programs", "black-wax", "--live-stream-url", "https://stream.example/3pbs.aac"];
+    assert!(Cli::try_parse_from(live_without_custom_serialization).is_err());
+    // Only the custom serialization tags episodes with their presenters.
+    assert!(Cli::try_parse_from(["pbsfm-rss-feed", "generate", "--programs", "black-wax", "--presenter-feeds"]).is_err());
+    assert!(Cli::try_parse_from(["pbsfm-rss-feed", "generate", "--programs", "black-wax", "--genre-feeds"]).is_err());
+    // The legacy feeds have no hub links, so hubs would never be discovered from them.
+    let websub_without_custom_serialization = [
+        "pbsfm-rss-feed", "generate", "--programs", "black-wax",
+        "--public-base-url", "https://feeds.example/", "--websub-hub", "https://hub.example/",
+    ];
+    assert!(Cli::try_parse_from(websub_without_custom_serialization).is_err());
+
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "openapi"]).unwrap();
+    assert!(matches!(cli.command, Some(Command::Openapi)));
+}
+
+#[test]
+fn test_parse_without_subcommand_generates() {
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "--programs", "black-wax,soul-time"]).unwrap();
+    assert!(cli.command.is_none());
+    assert_eq!(cli.args.programs, vec!["black-wax", "soul-time"]);
+}
+
+#[test]
+fn test_list_programs() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let programs = AirnetClient::new(server.base_url()).all_programs("3pbs")?;
+
+    let mut output = Vec::new();
+    commands::write_programs(&mut output, &programs, OutputFormat::Table)?;
+    assert_eq!(
+        String::from_utf8(output)?,
+        "Slug       Archived  Broadcasters\n\
+        -          yes\n\
+        black-wax  no        Adam Rudegeair\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_show_program() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let client = AirnetClient::new(server.base_url());
+    let program = client.program("3pbs", "black-wax")?;
+    let episodes = client.episodes("3pbs", "black-wax")?;
+
+    let mut output = Vec::new();
+    commands::write_program(&mut output, &program, &episodes, 1)?;
+    let output = String::from_utf8(output)?;
+    assert!(output.starts_with("Black Wax (black-wax)\nBroadcasters: Adam Rudegeair\n"));
+    assert!(output.contains("Image: https://profile-img.jpg\n"));
+    assert_eq!(output.lines().filter(|line| line.starts_with("  ")).count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let client = AirnetClient::new(server.base_url());
+    let now = Utc.with_ymd_and_hms(2025, 9, 1, 0, 0, 0).unwrap();
+    let mut playlists = PlaylistCache::default();
+    let programs = commands::fetch_program_stats(&client, &mut playlists, &["black-wax".to_owned()], now)?;
+    // The playlists are cached for the other outputs that need them.
+    assert!(playlists.get("black-wax", &mock_airnet::expected::episodes()[1].start).is_some());
+
+    let mut output = Vec::new();
+    stats::write_report(&mut output, &programs, ReportFormat::Csv)?;
+    let output = String::from_utf8(output)?;
+    let rows: Vec<&str> = output.lines().collect();
+    assert_eq!(rows.len(), 4);
+    assert!(rows[0].starts_with("program,slug,episode_start,episode_title,tracks,"));
+    assert!(rows[1].starts_with("Black Wax,black-wax,2025-06-16T11:00:00,"));
+    assert!(rows[3].starts_with("Black Wax,black-wax,,,19,"));
+    Ok(())
+}
+
+#[test]
+fn test_validate_expected_feeds() -> Result<(), Box<dyn std::error::Error>> {
+    let issues = validation::validate_file(Path::new("tests/expected-black-wax-v2.rss"))?;
+    // Airnet doesn't give the size of the recordings.
+    let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "warning: item \"Interview with Vince Jones and Jacob Collier!\" <enclosure> has no length",
+            "warning: item \"Untitled - 2025-08-25\" <enclosure> has no length",
+        ]
+    );
+    // The legacy serialization has no podcast-specific elements.
+    let issues = validation::validate_file(Path::new("tests/expected-black-wax.rss"))?;
+    assert!(!issues.is_empty() && !validation::has_errors(&issues));
+    commands::run_validate(ValidateArgs { file: "tests/expected-black-wax-v2.rss".into() })
+}
+
+#[test]
+fn test_validate_rejects_non_feed() {
+    assert!(validation::validate_file(Path::new("tests/mock_airnet/responses/all-programs.json")).is_err());
+}
@@ -1,5 +1,14 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use pbsfm_rss_feed::aggregate;
+use pbsfm_rss_feed::rss::{CategoryBuilder, ChannelBuilder, Enclosure, ItemBuilder, ItemGuidBuilder, PodcastPerson, Rss};
+
 mod mock_airnet;
 
+/// The day after the last mock episode aired.
+fn day_after_last_episode() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 8, 26, 2, 0, 0).unwrap()
+}
+
 #[test]
 fn test_convert_to_rss() -> Result<(), Box<dyn std::error::Error>> {
     let program = mock_airnet::expected::single_program();
@@ -39,4 +48,77 @@ fn test_fetch_and_convert() -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(rss_feed.items.len(), episodes.len());
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_combined_feed() {
+    let program = mock_airnet::expected::single_program();
+    let episodes = mock_airnet::expected::episodes();
+
+    let rss_feed =
+        aggregate::combined_feed(&[(program.clone(), episodes)], Duration::days(365), day_after_last_episode());
+
+    let category = CategoryBuilder::new("Black Wax")
+        .domain("https://www.pbsfm.org.au/program/black-wax".to_owned())
+        .build();
+    let items = vec![
+        ItemBuilder::with_title("Black Wax: Untitled - 2025-08-25")
+            .link("https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00".to_owned())
+            .guid(ItemGuidBuilder::new("https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-08-25+11%3A00%3A00").is_permalink(false).build())
+            .author(program.broadcasters.clone())
+            .description("some description".to_string())
+            .category(category.clone())
+            .enclosure(Enclosure::new(
+                "https://airnet.org.au/omnystudio/3pbs/black-wax/2025-08-25+11:00:00/aac_mid.m4a",
+                None,
+                "audio/mp4"
+            ))
+            .pub_date("Mon, 25 Aug 2025 11:00:00 +1000".to_owned())
+            .itunes_duration(7200u32)
+            .podcast_person(vec![PodcastPerson::host("Adam Rudegeair")])
+            .build(),
+        ItemBuilder::with_title("Black Wax: Interview with Vince Jones and Jacob Collier!")
+            .link("https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00".to_owned())
+            .guid(ItemGuidBuilder::new("https://airnet.org.au/rest/stations/3pbs/programs/black-wax/episodes/2025-06-16+11%3A00%3A00").is_permalink(false).build())
+            .author(program.broadcasters.clone())
+            .category(category)
+            .enclosure(Enclosure::new(
+                "https://airnet.org.au/omnystudio/3pbs/black-wax/2025-06-16+11:00:00/aac_mid.m4a",
+                None,
+                "audio/mp4"
+            ))
+            .pub_date("Mon, 16 Jun 2025 11:00:00 +1000".to_owned())
+            .itunes_duration(7200u32)
+            .podcast_person(vec![PodcastPerson::host("Adam Rudegeair")])
+            .build(),
+    ];
+
+    assert_eq!(
+        rss_feed,
+        Rss::new(
+            ChannelBuilder::new(
+                "PBS 106.7FM - All programs",
+                "https://www.pbsfm.org.au",
+                "Every program aired on PBS 106.7FM in the last 365 days",
+            )
+            .language("en".to_owned())
+            .item(items)
+            .build()
+        )
+    );
+}
+
+#[test]
+fn test_combined_feed_window() {
+    let program = mock_airnet::expected::single_program();
+    let episodes = mock_airnet::expected::episodes();
+
+    let programs = [(program, episodes)];
+
+    let rss_feed = aggregate::combined_feed(&programs, Duration::days(7), day_after_last_episode());
+    assert_eq!(rss_feed.channel().items().len(), 1);
+
+    // The window counts back from now, not from the last episode.
+    let month_later = Utc.with_ymd_and_hms(2025, 9, 26, 2, 0, 0).unwrap();
+    assert!(aggregate::combined_feed(&programs, Duration::days(7), month_later).channel().items().is_empty());
+}
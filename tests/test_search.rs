@@ -0,0 +1,152 @@
+use chrono::NaiveDate;
+use clap::Parser;
+use pbsfm_rss_feed::search::{self, OutputFormat, SearchArgs, SearchResult};
+use pbsfm_rss_feed::{Args, Cli, Command};
+use std::path::Path;
+use tempdir::TempDir;
+
+mod mock_airnet;
+
+fn write_archive(archive_path: &Path, output_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let server = mock_airnet::start_mock_airnet_server()?;
+    let args = Args {
+        use_custom_rss_serialization: true,
+        content_stats_in_description: true,
+        archive: Some(archive_path.to_path_buf()),
+        ..mock_airnet::base_args(output_dir, server.base_url())
+    };
+    pbsfm_rss_feed::run_app(args)
+}
+
+fn search_args(archive: &Path) -> SearchArgs {
+    SearchArgs {
+        archive: archive.to_path_buf(),
+        artist: None,
+        track: None,
+        release: None,
+        text: None,
+        programs: vec![],
+        from: None,
+        to: None,
+        format: OutputFormat::Table,
+    }
+}
+
+#[test]
+fn test_search_by_artist() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = TempDir::new("search")?;
+    let archive = tmp_dir.path().join("archive.sqlite");
+    write_archive(&archive, tmp_dir.path())?;
+
+    let results = search::search(&SearchArgs {
+        artist: Some("grabowsky".to_owned()),
+        release: Some("noir".to_owned()),
+        ..search_args(&archive)
+    })?;
+
+    assert_eq!(
+        results.iter().map(|result| result.track.as_deref()).collect::<Vec<_>>(),
+        vec![Some("I'm a Fool to Want You"), Some("Rainbow Cake")]
+    );
+    assert_eq!(results[0].artist.as_deref(), Some("Paul Grabowsky and Michelle Nicolle"));
+    assert_eq!(
+        results[0].enclosure_url,
+        "https://airnet.org.au/omnystudio/3pbs/black-wax/2025-08-25+11:00:00/aac_mid.m4a"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_search_by_track_reports_time_in_episode() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = TempDir::new("search")?;
+    let archive = tmp_dir.path().join("archive.sqlite");
+    write_archive(&archive, tmp_dir.path())?;
+
+    let results = search::search(&SearchArgs {
+        track: Some("black wax intro".to_owned()),
+        ..search_args(&archive)
+    })?;
+
+    assert_eq!(
+        results,
+        vec![SearchResult {
+            program: "black-wax".to_owned(),
+            program_name: Some("Black Wax".to_owned()),
+            aired: NaiveDate::from_ymd_opt(2025, 8, 25).unwrap().and_hms_opt(11, 0, 0).unwrap(),
+            episode: "Untitled - 2025-08-25".to_owned(),
+            artist: Some("Various Artists".to_owned()),
+            track: Some("Black Wax Intro".to_owned()),
+            release: None,
+            time_in_episode: Some("0:00:00".to_owned()),
+            link: "https://www.pbsfm.org.au/program/black-wax/2025-08-25/11-00-00".to_owned(),
+            enclosure_url: "https://airnet.org.au/omnystudio/3pbs/black-wax/2025-08-25+11:00:00/aac_mid.m4a".to_owned(),
+        }]
+    );
+
+    let mut table = Vec::new();
+    search::write_results(&mut table, &results, OutputFormat::Table)?;
+    assert_eq!(
+        String::from_utf8(table)?,
+        "Program    Aired             Episode                Time     Track                              Enclosure\n\
+        black-wax  2025-08-25 11:00  Untitled - 2025-08-25  0:00:00  Various Artists - Black Wax Intro  \
+        https://airnet.org.au/omnystudio/3pbs/black-wax/2025-08-25+11:00:00/aac_mid.m4a\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_search_episodes_by_text_and_date() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_dir = TempDir::new("search")?;
+    let archive = tmp_dir.path().join("archive.sqlite");
+    write_archive(&archive, tmp_dir.path())?;
+
+    let interviews = search::search(&SearchArgs {
+        text: Some("INTERVIEW".to_owned()),
+        ..search_args(&archive)
+    })?;
+    assert_eq!(interviews.len(), 1);
+    assert_eq!(interviews[0].episode, "Interview with Vince Jones and Jacob Collier!");
+    assert_eq!(interviews[0].time_in_episode, None);
+
+    let in_august = search::search(&SearchArgs {
+        from: NaiveDate::from_ymd_opt(2025, 8, 1),
+        to: NaiveDate::from_ymd_opt(2025, 8, 25),
+        programs: vec!["black-wax".to_owned()],
+        ..search_args(&archive)
+    })?;
+    assert_eq!(in_august.len(), 1);
+    assert_eq!(in_august[0].episode, "Untitled - 2025-08-25");
+
+    let other_program = search::search(&SearchArgs {
+        programs: vec!["soul-time".to_owned()],
+        ..search_args(&archive)
+    })?;
+    assert_eq!(other_program, vec![]);
+    Ok(())
+}
+
+#[test]
+fn test_search_fails_without_archive() {
+    let tmp_dir = TempDir::new("search").unwrap();
+    let result = search::search(&search_args(&tmp_dir.path().join("missing.sqlite")));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_search_subcommand() {
+    let cli = Cli::try_parse_from([
+        "pbsfm-rss-feed", "search", "--archive", "archive.sqlite", "--artist", "Vince Jones", "--format", "json",
+    ])
+    .unwrap();
+    match cli.command {
+        Some(Command::Search(args)) => {
+            assert_eq!(args.artist.as_deref(), Some("Vince Jones"));
+            assert_eq!(args.format, OutputFormat::Json);
+        }
+        command => panic!("expected search, got {command:?}"),
+    }
+
+    let cli = Cli::try_parse_from(["pbsfm-rss-feed", "--programs", "black-wax,soul-time"]).unwrap();
+    assert!(cli.command.is_none());
+    assert_eq!(cli.args.programs, vec!["black-wax", "soul-time"]);
+}
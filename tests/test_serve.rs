@@ -0,0 +1,228 @@
+use pbsfm_rss_feed::serve::{FeedRequest, FeedServer};
+use reqwest::StatusCode;
+use reqwest::blocking::Client;
+use std::fs;
+use std::time::Duration;
+use tempdir::TempDir;
+use tiny_http::Server;
+
+mod mock_airnet;
+
+/// Starts a feed server on a free port, returning its base URL.
+fn start_feed_server(airnet_url: String, programs: Vec<String>) -> String {
+    start_feed_server_with_cors(airnet_url, programs, vec![])
+}
+
+fn start_feed_server_with_cors(airnet_url: String, programs: Vec<String>, cors_origins: Vec<String>) -> String {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let address = server.server_addr().to_ip().unwrap();
+    std::thread::spawn(move || {
+        FeedServer::new(airnet_url, Duration::from_secs(60), programs)
+            .with_cors_origins(cors_origins)
+            .serve(&server)
+    });
+    format!("http://{address}")
+}
+
+/// The expected Black Wax feed, linking to the URL it was served at.
+fn expected_black_wax(self_url: &str) -> std::io::Result<String> {
+    let self_link = format!(r#"    <atom:link href="{self_url}" rel="self" type="application/rss+xml"/>"#);
+    Ok(fs::read_to_string("tests/expected-black-wax-v2.rss")?
+        .replace(
+            r#"xmlns:podcast="https://podcastindex.org/namespace/1.0">"#,
+            r#"xmlns:podcast="https://podcastindex.org/namespace/1.0" xmlns:atom="http://www.w3.org/2005/Atom">"#,
+        )
+        .replacen("    <item>\n", &format!("{self_link}\n    <item>\n"), 1))
+}
+
+#[test]
+fn test_serve_feed() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let base_url = start_feed_server(airnet.base_url(), vec![]);
+    let client = Client::new();
+
+    let response = client.get(format!("{base_url}/feeds/pbsfm/black-wax/rss.xml")).send()?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["Content-Type"], "application/rss+xml; charset=utf-8");
+    let etag = response.headers()["ETag"].to_str()?.to_owned();
+    assert_eq!(response.text()?, expected_black_wax(&format!("{base_url}/feeds/pbsfm/black-wax/rss.xml"))?);
+
+    let response = client
+        .get(format!("{base_url}/feeds/pbsfm/black-wax/rss.xml"))
+        .header("If-None-Match", &etag)
+        .send()?;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()["ETag"].to_str()?, etag);
+    Ok(())
+}
+
+#[test]
+fn test_serve_from_cache() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let base_url = start_feed_server(airnet.base_url(), vec![]);
+    let client = Client::new();
+
+    let first = client.get(format!("{base_url}/feeds/pbsfm/black-wax/rss.xml")).send()?.text()?;
+    // Within the TTL the feed is served without asking Airnet again.
+    airnet.reset();
+    let response = client.get(format!("{base_url}/feeds/pbsfm/black-wax/rss.xml?utm=1")).send()?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text()?, first);
+
+    let response = client.get(format!("{base_url}/feeds/pbsfm/soul-time/rss.xml")).send()?;
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    Ok(())
+}
+
+#[test]
+fn test_serve_expired_feed_when_airnet_fails() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let mut server = FeedServer::new(airnet.base_url(), Duration::ZERO, vec![]);
+    let request = FeedRequest::new("GET", "/feeds/pbsfm/black-wax/rss.xml");
+
+    let first = server.respond(&request);
+    assert_eq!(first.status, 200);
+    airnet.reset();
+    let expired = server.respond(&request);
+    assert_eq!(expired.status, 200);
+    assert_eq!(expired.body, first.body);
+    assert!(expired.header("ETag").is_some_and(|etag| etag.starts_with("\"sha256:")));
+    Ok(())
+}
+
+#[test]
+fn test_serve_keeps_published_guids() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let output_dir = TempDir::new("feeds")?;
+    let feed_dir = output_dir.path().join("pbsfm/black-wax");
+    fs::create_dir_all(&feed_dir)?;
+    fs::copy("tests/published-black-wax-v2.rss", feed_dir.join("rss.xml"))?;
+    let mut server = FeedServer::new(airnet.base_url(), Duration::from_secs(60), vec![])
+        .with_published_feeds(output_dir.path().to_path_buf());
+
+    let response = server.respond(&FeedRequest::new("GET", "/feeds/pbsfm/black-wax/rss.xml"));
+    let feed = String::from_utf8(response.body)?;
+    assert!(feed.contains("<guid>https://www.pbsfm.org.au/program/black-wax/2025-06-16/11-00-00</guid>"));
+    Ok(())
+}
+
+#[test]
+fn test_serve_not_found() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let base_url = start_feed_server(airnet.base_url(), vec!["soul-time".to_owned()]);
+    let client = Client::new();
+
+    for path in ["/feeds/pbsfm/black-wax/rss.xml", "/feeds/pbsfm/black-wax", "/feeds/pbsfm/soul-time/atom.xml", "/"] {
+        assert_eq!(client.get(format!("{base_url}{path}")).send()?.status(), StatusCode::NOT_FOUND);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_serve_negotiated_formats() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let base_url = start_feed_server(airnet.base_url(), vec![]);
+    let client = Client::new();
+    let feed_url = format!("{base_url}/feeds/pbsfm/black-wax");
+
+    let response = client.get(&feed_url).send()?;
+    assert_eq!(response.headers()["Content-Type"], "application/rss+xml; charset=utf-8");
+    assert_eq!(response.headers()["Vary"], "Accept");
+    assert_eq!(response.text()?, expected_black_wax(&feed_url)?);
+
+    let response = client.get(&feed_url).header("Accept", "application/atom+xml").send()?;
+    assert_eq!(response.headers()["Content-Type"], "application/atom+xml; charset=utf-8");
+    let atom = response.text()?;
+    assert!(atom.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+    assert!(atom.contains("<updated>2025-06-16T11:00:00+10:00</updated>"));
+    assert!(atom.contains(&format!(r#"<link href="{feed_url}" rel="self" type="application/atom+xml"/>"#)));
+
+    // The parameter wins over the header, for links that can't set headers.
+    let response = client
+        .get(format!("{feed_url}?format=json"))
+        .header("Accept", "application/atom+xml")
+        .send()?;
+    assert_eq!(response.headers()["Content-Type"], "application/feed+json; charset=utf-8");
+    let json: serde_json::Value = response.json()?;
+    assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+    assert_eq!(json["title"], "Black Wax");
+    assert_eq!(json["feed_url"], feed_url);
+    assert_eq!(json["items"][0]["date_published"], "2025-06-16T11:00:00+10:00");
+
+    let response = client.get(&feed_url).header("Accept", "text/html").send()?;
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    Ok(())
+}
+
+#[test]
+fn test_serve_cors() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let base_url = start_feed_server_with_cors(airnet.base_url(), vec![], vec!["https://dashboard.example".to_owned()]);
+    let client = Client::new();
+    let feed_url = format!("{base_url}/feeds/pbsfm/black-wax");
+
+    let response = client.get(&feed_url).header("Origin", "https://dashboard.example").send()?;
+    assert_eq!(response.headers()["Access-Control-Allow-Origin"], "https://dashboard.example");
+    assert_eq!(response.headers()["Access-Control-Expose-Headers"], "ETag");
+    assert_eq!(response.headers()["Vary"], "Accept, Origin");
+
+    let response = client.get(&feed_url).header("Origin", "https://other.example").send()?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("Access-Control-Allow-Origin").is_none());
+    assert_eq!(response.headers()["Vary"], "Accept, Origin");
+    Ok(())
+}
+
+#[test]
+fn test_serve_api() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let base_url = start_feed_server(airnet.base_url(), vec![]);
+    let client = Client::new();
+
+    let response = client.get(format!("{base_url}/api/programs")).send()?;
+    assert_eq!(response.headers()["Content-Type"], "application/json");
+    let programs: serde_json::Value = response.json()?;
+    assert_eq!(programs[1]["slug"], "black-wax");
+    assert_eq!(programs[1]["link"], "https://www.pbsfm.org.au/program/black-wax");
+
+    let program: serde_json::Value = client.get(format!("{base_url}/api/programs/black-wax")).send()?.json()?;
+    assert_eq!(program["artwork_url"], "https://profile-img.jpg");
+
+    let episodes: serde_json::Value = client.get(format!("{base_url}/api/programs/black-wax/episodes")).send()?.json()?;
+    assert_eq!(episodes[0]["id"], "black-wax.20250616T110000");
+    assert_eq!(episodes[0]["start"], "2025-06-16T11:00:00+10:00");
+    assert_eq!(
+        episodes[0]["enclosure_url"],
+        "https://airnet.org.au/omnystudio/3pbs/black-wax/2025-06-16+11:00:00/aac_mid.m4a"
+    );
+    assert_eq!(episodes[1]["image_url"], "http://img-url");
+
+    let playlist_url = episodes[1]["playlist_url"].as_str().unwrap();
+    let playlist: serde_json::Value = client.get(format!("{base_url}{playlist_url}")).send()?.json()?;
+    assert_eq!(playlist[0]["artist"], "Various Artists");
+    assert_eq!(playlist[0]["approximate_time"], "2025-08-25T11:00:00+10:00");
+
+    let document: serde_json::Value = client.get(format!("{base_url}/api/openapi.json")).send()?.json()?;
+    assert_eq!(document["openapi"], "3.1.0");
+    Ok(())
+}
+
+#[test]
+fn test_serve_api_allowed_programs() -> Result<(), Box<dyn std::error::Error>> {
+    let airnet = mock_airnet::start_mock_airnet_server()?;
+    let base_url = start_feed_server(airnet.base_url(), vec!["soul-time".to_owned()]);
+    let client = Client::new();
+
+    for path in ["/api/programs/black-wax/episodes", "/api/episodes/black-wax.20250616T110000/playlist", "/api/nothing"] {
+        assert_eq!(client.get(format!("{base_url}{path}")).send()?.status(), StatusCode::NOT_FOUND);
+    }
+    let response = client.get(format!("{base_url}/api/programs")).send()?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.json::<serde_json::Value>()?, serde_json::json!([]));
+
+    let base_url = start_feed_server(airnet.base_url(), vec!["black-wax".to_owned()]);
+    let programs: serde_json::Value = client.get(format!("{base_url}/api/programs")).send()?.json()?;
+    let slugs: Vec<&str> = programs.as_array().unwrap().iter().filter_map(|program| program["slug"].as_str()).collect();
+    assert_eq!(slugs, vec!["black-wax"]);
+    Ok(())
+}